@@ -3,36 +3,39 @@
 typedef char literal[256];
 typedef int inteiro;
 typedef double real;
+typedef int logico;
 
-void main(void)
+literal v_A;
+inteiro v_B;
+inteiro v_D;
+real v_C;
+
+int main(void)
 {
     /*----Variaveis temporarias----*/
-    real T0
-    inteiro T1
-    inteiro T2
-    inteiro T3
+    inteiro T1;
     /*------------------------------*/
-    literal A;
-    inteiro B;
-    inteiro D;
-    real C;
-
-    T0 = C + 2e0;
-    C = T0;
+    printf("Digite B:");
+    scanf("%d", &v_B);
     printf("Digite A:");
-    scanf("%s", A);
-    T1 = B > 2;
-    if (T1) {
-    scanf("%d", &B);
-    T2 = B <= D;
-    if (T2) {
-    printf("B esta entre 2 e 4");
-    }
+    scanf("%255s", v_A);
+    if (v_B > 2) {
+        if (v_B <= 4) {
+            printf("B esta entre 2 e 4");
+        }
     }
-    T3 = C < 5;
-    for(; T3; T3 = C < 5) {
-    printf("%lf", C);
-    scanf("%lf", &C);
+    T1 = v_B + 1;
+    v_B = T1;
+    T1 = v_B + 2;
+    v_B = T1;
+    T1 = v_B + 3;
+    v_B = T1;
+    v_D = v_B;
+    v_C = 5.0;
+    while (v_B <= 5) {
+        T1 = v_B + 1;
+        v_B = T1;
     }
+    printf("\nB=%d\nC=%lf\n%s\n", v_D, v_C, v_A);
     return 0;
 }
@@ -0,0 +1,745 @@
+// The x86-64 assembly (GNU as, AT&T syntax) of a program, generated
+// from its intermediate code, for Linux. It reads and writes with
+// scanf and printf from the C library, so cc assembles and links it.
+//
+// Every variable of a subprogram (or of the main program) is a slot of
+// its frame, below %rbp: the parameters, the local variables and the
+// temporary variables, each in 8 bytes, except a literal (256 bytes)
+// or an array. The program variables are in .bss. An inteiro or a
+// logico is a 32 bit integer and a real is a double, computed with
+// SSE2.
+//
+// A subprogram gets its arguments in 8 bytes each on the stack, from
+// 16(%rbp) up, and copies them to its slots; a literal argument is
+// the address of the literal. It returns its value in %eax, %xmm0 or,
+// for a literal, the address in %rax.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use super::interpreter::unescape;
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// the bytes of a literal, with the '\0' that ends it
+const LITERAL_SIZE: usize = 256;
+
+// the bytes one value of the type takes in an array
+fn byte_size(tk_type: Type) -> usize {
+    match tk_type {
+        Type::Inteiro | Type::Logico => 4,
+        Type::Real => 8,
+        Type::Literal => LITERAL_SIZE,
+    }
+}
+
+// the bytes of a variable, rounded up so the next one is aligned
+fn var_size(var: &Var) -> usize {
+    match var.size {
+        Some(size) => (byte_size(var.tk_type) * size).div_ceil(8) * 8,
+        None if var.tk_type == Type::Literal => LITERAL_SIZE,
+        None => 8,
+    }
+}
+
+// a text as a GNU as string, with the bytes that are not printable ASCII in octal
+fn asm_string(text: &str) -> String {
+    let mut s = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'"' | b'\\' => s.push_str(&format!("\\{}", byte as char)),
+            b' '..=b'~' => s.push(byte as char),
+            _ => s.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    s
+}
+
+// functions of the generated code, written in the assembly only when
+// they are used
+#[derive(PartialEq)]
+enum Helper {
+    CopyLiteral,
+    IndexError,
+    DivisionError,
+    ReadLogico,
+}
+
+pub struct AsmFile {
+    // the literal and real constants, in .rodata
+    literals: Vec<String>,
+    reals: Vec<f64>,
+    globals: Vec<Var>,
+    functions: Vec<Vec<String>>,
+    helpers: Vec<Helper>,
+}
+
+// the code of a subprogram (or of the main program) being generated
+struct Body<'a, 'f> {
+    asm_file: &'f mut AsmFile,
+    program: &'a Program,
+    function: &'a Function,
+    // the offset from %rbp of each slot of the frame, for the variables
+    // and for the temporary variables
+    slots: HashMap<String, i64>,
+    temp_slots: Vec<i64>,
+    lines: Vec<String>,
+}
+
+impl<'a, 'f> Body<'a, 'f> {
+    fn emit(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("    {}", line.into()));
+    }
+
+    // where a name used in the function is and its declaration
+    fn var(&self, name: &str) -> (String, Var) {
+        let var = match self.program.get_var(self.function, name) {
+            Some(var) => var.clone(),
+            None => panic!("Variável '{}' não declarada no código intermediário", name),
+        };
+        match self.slots.get(name) {
+            Some(offset) => (format!("{}(%rbp)", offset), var),
+            None => (format!("v_{}(%rip)", name), var),
+        }
+    }
+
+    fn label(&self, label: usize) -> String {
+        format!(".L{}_{}", self.function.name, label)
+    }
+
+    // put the address of an array element in %r9, stopping the program
+    // when the index is out of bounds
+    fn element(&mut self, array: &str, index: &Operand) {
+        self.load_int(index, "%r8d");
+        let (place, var) = self.var(array);
+        let size = var.size.unwrap_or(0);
+        self.emit(format!("cmpl ${}, %r8d", size));
+        self.emit("jb 1f");
+        self.emit("movl %r8d, %edi");
+        self.emit(format!("movl ${}, %esi", size));
+        self.emit("xorl %edx, %edx");
+        self.asm_file.use_helper(Helper::IndexError);
+        self.emit("call mgol_erro_indice");
+        self.lines.push(String::from("1:"));
+        self.emit("movslq %r8d, %r8");
+        self.emit(format!("leaq {}, %r9", place));
+        match byte_size(var.tk_type) {
+            LITERAL_SIZE => {
+                self.emit("salq $8, %r8");
+                self.emit("addq %r8, %r9");
+            }
+            scale => self.emit(format!("leaq (%r9,%r8,{}), %r9", scale)),
+        }
+    }
+
+    // the place of an operand that is a variable or an array element
+    fn place(&mut self, operand: &Operand) -> (String, Var) {
+        match operand {
+            Operand::Index(array, index) => {
+                self.element(array, index);
+                let (_, var) = self.var(array);
+                (String::from("(%r9)"), var)
+            }
+            Operand::Var(name) => self.var(name),
+            Operand::Temp(n) => (
+                format!("{}(%rbp)", self.temp_slots[*n]),
+                Var {
+                    name: operand.to_string(),
+                    tk_type: self.function.temps[*n],
+                    size: None,
+                },
+            ),
+            _ => panic!("Uma constante foi usada como variável no código intermediário"),
+        }
+    }
+
+    // put an operand in a 32 bit register, as an inteiro
+    fn load_int(&mut self, operand: &Operand, register: &str) {
+        match operand {
+            Operand::Int(n) => self.emit(format!("movl ${}, {}", n, register)),
+            Operand::Real(x) => self.emit(format!("movl ${}, {}", *x as i32, register)),
+            Operand::Lit(_) => panic!("Um literal foi usado como número no código intermediário"),
+            _ => {
+                let (place, var) = self.place(operand);
+                match var.tk_type {
+                    Type::Real => self.emit(format!("cvttsd2si {}, {}", place, register)),
+                    _ => self.emit(format!("movl {}, {}", place, register)),
+                }
+            }
+        }
+    }
+
+    // put an operand in an SSE register, as a real
+    fn load_real(&mut self, operand: &Operand, register: &str) {
+        match operand {
+            Operand::Int(n) => {
+                let n = self.asm_file.real(*n as f64);
+                self.emit(format!("movsd .LR{}(%rip), {}", n, register));
+            }
+            Operand::Real(x) => {
+                let n = self.asm_file.real(*x);
+                self.emit(format!("movsd .LR{}(%rip), {}", n, register));
+            }
+            Operand::Lit(_) => panic!("Um literal foi usado como número no código intermediário"),
+            _ => {
+                let (place, var) = self.place(operand);
+                match var.tk_type {
+                    Type::Real => self.emit(format!("movsd {}, {}", place, register)),
+                    _ => self.emit(format!("cvtsi2sdl {}, {}", place, register)),
+                }
+            }
+        }
+    }
+
+    // put the address of a literal in a 64 bit register
+    fn load_address(&mut self, operand: &Operand, register: &str) {
+        match operand {
+            Operand::Lit(text) => {
+                let n = self.asm_file.literal(unescape(text));
+                self.emit(format!("leaq .LS{}(%rip), {}", n, register));
+            }
+            _ => {
+                let (place, _) = self.place(operand);
+                self.emit(format!("leaq {}, {}", place, register));
+            }
+        }
+    }
+
+    // put a value of type tk_type in %eax, %xmm0 or, for a literal, its
+    // address in %rax
+    fn load(&mut self, operand: &Operand, tk_type: Type) {
+        match tk_type {
+            Type::Real => self.load_real(operand, "%xmm0"),
+            Type::Literal => self.load_address(operand, "%rax"),
+            _ => self.load_int(operand, "%eax"),
+        }
+    }
+
+    // store the value load left, of type tk_type, in target
+    fn store(&mut self, target: &Operand, tk_type: Type) {
+        let (place, var) = self.place(target);
+        match (tk_type, var.tk_type) {
+            (_, Type::Literal) => {
+                self.emit("movq %rax, %rsi");
+                self.emit(format!("leaq {}, %rdi", place));
+                self.asm_file.use_helper(Helper::CopyLiteral);
+                self.emit("call mgol_copia");
+            }
+            (Type::Real, Type::Real) => self.emit(format!("movsd %xmm0, {}", place)),
+            (_, Type::Real) => {
+                self.emit("cvtsi2sdl %eax, %xmm0");
+                self.emit(format!("movsd %xmm0, {}", place));
+            }
+            (Type::Real, _) => {
+                self.emit("cvttsd2si %xmm0, %eax");
+                self.emit(format!("movl %eax, {}", place));
+            }
+            _ => self.emit(format!("movl %eax, {}", place)),
+        }
+    }
+
+    // left operator right, with left in %eax (or %xmm0) and right in
+    // %ecx (or %xmm1); the result is left in %eax or %xmm0
+    fn operator(&mut self, operator: Operator, tk_type: Type) {
+        if tk_type == Type::Real {
+            // the comparisons are false when a value is not a number,
+            // so < and <= compare the other way round
+            let set = match operator {
+                Operator::Add => return self.emit("addsd %xmm1, %xmm0"),
+                Operator::Sub => return self.emit("subsd %xmm1, %xmm0"),
+                Operator::Mul => return self.emit("mulsd %xmm1, %xmm0"),
+                Operator::Div => return self.emit("divsd %xmm1, %xmm0"),
+                Operator::Gt => "seta %al",
+                Operator::Ge => "setae %al",
+                Operator::Lt => "seta %al",
+                Operator::Le => "setae %al",
+                Operator::Eq => "sete %al",
+                Operator::Ne => "setne %al",
+                Operator::And | Operator::Or => {
+                    panic!("Um operador lógico foi usado com reais no código intermediário")
+                }
+            };
+            match operator {
+                Operator::Lt | Operator::Le => self.emit("ucomisd %xmm0, %xmm1"),
+                _ => self.emit("ucomisd %xmm1, %xmm0"),
+            }
+            self.emit(set);
+            match operator {
+                Operator::Eq => {
+                    self.emit("setnp %cl");
+                    self.emit("andb %cl, %al");
+                }
+                Operator::Ne => {
+                    self.emit("setp %cl");
+                    self.emit("orb %cl, %al");
+                }
+                _ => (),
+            }
+            self.emit("movzbl %al, %eax");
+            return;
+        }
+
+        let set = match operator {
+            Operator::Add => return self.emit("addl %ecx, %eax"),
+            Operator::Sub => return self.emit("subl %ecx, %eax"),
+            Operator::Mul => return self.emit("imull %ecx, %eax"),
+            Operator::Div => {
+                // the division of the smallest inteiro by -1 would stop
+                // the program, so it is a negation
+                self.emit("testl %ecx, %ecx");
+                self.emit("jne 1f");
+                self.asm_file.use_helper(Helper::DivisionError);
+                self.emit("call mgol_erro_divisao");
+                self.lines.push(String::from("1:"));
+                self.emit("cmpl $-1, %ecx");
+                self.emit("jne 2f");
+                self.emit("negl %eax");
+                self.emit("jmp 3f");
+                self.lines.push(String::from("2:"));
+                self.emit("cltd");
+                self.emit("idivl %ecx");
+                self.lines.push(String::from("3:"));
+                return;
+            }
+            Operator::Lt => "setl %al",
+            Operator::Le => "setle %al",
+            Operator::Gt => "setg %al",
+            Operator::Ge => "setge %al",
+            Operator::Eq => "sete %al",
+            Operator::Ne => "setne %al",
+            // a logico is 1 or 0
+            Operator::And => return self.emit("andl %ecx, %eax"),
+            Operator::Or => return self.emit("orl %ecx, %eax"),
+        };
+        self.emit("cmpl %ecx, %eax");
+        self.emit(set);
+        self.emit("movzbl %al, %eax");
+    }
+
+    // call printf or scanf with the format string and the arguments
+    // already in the registers
+    fn call_libc(&mut self, function: &str, format: &str, reals: usize) {
+        self.emit(format!("leaq {}(%rip), %rdi", format));
+        self.emit(format!("movl ${}, %eax", reals));
+        self.emit(format!("call {}@PLT", function));
+    }
+
+    fn leave(&mut self) {
+        self.emit("leave");
+        self.emit("ret");
+    }
+
+    fn code(&mut self) {
+        let (program, function) = (self.program, self.function);
+
+        for instr in &function.code {
+            match instr {
+                Instr::Assign(target, value, _) => {
+                    let value_type = program.type_of(function, value);
+                    self.load(value, value_type);
+                    self.store(target, value_type);
+                }
+                Instr::BinOp(target, left, operator, right, tk_type) => {
+                    if *tk_type == Type::Real {
+                        self.load_real(left, "%xmm0");
+                        self.load_real(right, "%xmm1");
+                    } else {
+                        self.load_int(left, "%eax");
+                        self.load_int(right, "%ecx");
+                    }
+                    self.operator(*operator, *tk_type);
+                    let result = if operator.is_relational() {
+                        Type::Logico
+                    } else {
+                        *tk_type
+                    };
+                    self.store(target, result);
+                }
+                Instr::Read(target, tk_type) => {
+                    // scanf leaves the variable as it is when there is
+                    // nothing to read
+                    let (place, _) = self.place(target);
+                    match tk_type {
+                        Type::Logico => {
+                            self.emit(format!("leaq {}, %rdi", place));
+                            self.asm_file.use_helper(Helper::ReadLogico);
+                            self.emit("call mgol_leia_logico");
+                        }
+                        _ => {
+                            self.emit(format!("leaq {}, %rsi", place));
+                            let format = match tk_type {
+                                Type::Real => ".Lformato_real",
+                                Type::Literal => ".Lformato_leia_literal",
+                                _ => ".Lformato_inteiro",
+                            };
+                            self.call_libc("scanf", format, 0);
+                        }
+                    }
+                }
+                Instr::Write(value, tk_type) => match tk_type {
+                    Type::Inteiro => {
+                        self.load_int(value, "%esi");
+                        self.call_libc("printf", ".Lformato_inteiro", 0);
+                    }
+                    Type::Real => {
+                        self.load_real(value, "%xmm0");
+                        self.call_libc("printf", ".Lformato_real", 1);
+                    }
+                    Type::Literal => {
+                        self.load_address(value, "%rsi");
+                        self.call_libc("printf", ".Lformato_literal", 0);
+                    }
+                    // a logico is written as the word verdadeiro or falso
+                    Type::Logico => {
+                        self.load_int(value, "%eax");
+                        self.emit("leaq .Lverdadeiro(%rip), %rsi");
+                        self.emit("leaq .Lfalso(%rip), %rdx");
+                        self.emit("testl %eax, %eax");
+                        self.emit("cmovzq %rdx, %rsi");
+                        self.call_libc("printf", ".Lformato_literal", 0);
+                    }
+                },
+                Instr::Label(label) => {
+                    let label = self.label(*label);
+                    self.lines.push(format!("{}:", label));
+                }
+                Instr::Goto(label) => {
+                    let label = self.label(*label);
+                    self.emit(format!("jmp {}", label));
+                }
+                Instr::IfFalse(condition, label) => {
+                    self.load_int(condition, "%eax");
+                    self.emit("testl %eax, %eax");
+                    let label = self.label(*label);
+                    self.emit(format!("je {}", label));
+                }
+                Instr::Call(target, name, args) => {
+                    let callee = program
+                        .subprograms
+                        .iter()
+                        .find(|function| function.name == *name)
+                        .unwrap_or_else(|| panic!("Subprograma '{}' não definido no código intermediário", name));
+                    // the stack stays aligned to 16 bytes
+                    let area = (8 * args.len()).div_ceil(16) * 16;
+                    if area > 0 {
+                        self.emit(format!("subq ${}, %rsp", area));
+                    }
+                    for (i, (arg, param)) in args.iter().zip(&callee.params).enumerate() {
+                        self.load(arg, param.tk_type);
+                        self.emit(match param.tk_type {
+                            Type::Real => format!("movsd %xmm0, {}(%rsp)", 8 * i),
+                            Type::Literal => format!("movq %rax, {}(%rsp)", 8 * i),
+                            _ => format!("movl %eax, {}(%rsp)", 8 * i),
+                        });
+                    }
+                    self.emit(format!("call f_{}", name));
+                    if area > 0 {
+                        self.emit(format!("addq ${}, %rsp", area));
+                    }
+                    if let (Some(target), Some(return_type)) = (target, callee.return_type) {
+                        self.store(target, return_type);
+                    }
+                }
+                Instr::Return(value) => {
+                    if let Some(value) = value {
+                        self.load(value, function.return_type.unwrap_or(Type::Inteiro));
+                    } else if function.name == program.main.name {
+                        self.emit("xorl %eax, %eax");
+                    }
+                    self.leave();
+                }
+                Instr::CheckIndex(index, size, row) => {
+                    self.load_int(index, "%r8d");
+                    self.emit(format!("cmpl ${}, %r8d", size));
+                    self.emit("jb 1f");
+                    self.emit("movl %r8d, %edi");
+                    self.emit(format!("movl ${}, %esi", size));
+                    self.emit(format!("movl ${}, %edx", row));
+                    self.asm_file.use_helper(Helper::IndexError);
+                    self.emit("call mgol_erro_indice");
+                    self.lines.push(String::from("1:"));
+                }
+            }
+        }
+
+        // a function that ends without returning a value returns the
+        // zero of its type, and the main program returns 0
+        match function.return_type {
+            Some(Type::Real) => self.emit("pxor %xmm0, %xmm0"),
+            Some(_) => self.emit("xorl %eax, %eax"),
+            None if function.name == program.main.name => self.emit("xorl %eax, %eax"),
+            None => (),
+        }
+        self.leave();
+    }
+}
+
+impl AsmFile {
+    pub fn new(program: &Program) -> AsmFile {
+        let mut asm_file = AsmFile {
+            literals: Vec::new(),
+            reals: Vec::new(),
+            globals: program.globals.clone(),
+            functions: Vec::new(),
+            helpers: Vec::new(),
+        };
+
+        for function in &program.subprograms {
+            let lines = asm_file.function(program, function, format!("f_{}", function.name));
+            asm_file.functions.push(lines);
+        }
+        let lines = asm_file.function(program, &program.main, String::from("main"));
+        asm_file.functions.push(lines);
+
+        asm_file
+    }
+
+    fn use_helper(&mut self, helper: Helper) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+        }
+    }
+
+    // the number of a literal constant, .LSn
+    fn literal(&mut self, text: String) -> usize {
+        match self.literals.iter().position(|literal| *literal == text) {
+            Some(n) => n,
+            None => {
+                self.literals.push(text);
+                self.literals.len() - 1
+            }
+        }
+    }
+
+    // the number of a real constant, .LRn
+    fn real(&mut self, x: f64) -> usize {
+        match self.reals.iter().position(|real| real.to_bits() == x.to_bits()) {
+            Some(n) => n,
+            None => {
+                self.reals.push(x);
+                self.reals.len() - 1
+            }
+        }
+    }
+
+    fn function(&mut self, program: &Program, function: &Function, symbol: String) -> Vec<String> {
+        let mut slots = HashMap::new();
+        let mut frame_size = 0;
+        for var in function.params.iter().chain(&function.locals) {
+            frame_size += var_size(var);
+            slots.insert(var.name.clone(), -(frame_size as i64));
+        }
+        let mut temp_slots = Vec::new();
+        for tk_type in &function.temps {
+            frame_size += var_size(&Var {
+                name: String::new(),
+                tk_type: *tk_type,
+                size: None,
+            });
+            temp_slots.push(-(frame_size as i64));
+        }
+        // the stack stays aligned to 16 bytes
+        let frame_size = frame_size.div_ceil(16) * 16;
+
+        let mut lines = Vec::new();
+        if symbol == "main" {
+            lines.push(String::from("    .globl main"));
+        }
+        lines.push(format!("    .type {}, @function", symbol));
+        lines.push(format!("{}:", symbol));
+        let mut body = Body {
+            asm_file: self,
+            program,
+            function,
+            slots,
+            temp_slots,
+            lines,
+        };
+        body.emit("pushq %rbp");
+        body.emit("movq %rsp, %rbp");
+        if frame_size > 0 {
+            // the frame starts with zeros, like every variable
+            body.emit(format!("subq ${}, %rsp", frame_size));
+            body.emit("movq %rsp, %rdi");
+            body.emit(format!("movl ${}, %ecx", frame_size / 8));
+            body.emit("xorl %eax, %eax");
+            body.emit("rep stosq");
+        }
+        for (i, param) in function.params.iter().enumerate() {
+            let (place, _) = body.var(&param.name);
+            let arg = format!("{}(%rbp)", 16 + 8 * i);
+            match param.tk_type {
+                Type::Real => {
+                    body.emit(format!("movsd {}, %xmm0", arg));
+                    body.emit(format!("movsd %xmm0, {}", place));
+                }
+                // a literal argument is copied into the frame
+                Type::Literal => {
+                    body.emit(format!("movq {}, %rsi", arg));
+                    body.emit(format!("leaq {}, %rdi", place));
+                    body.asm_file.use_helper(Helper::CopyLiteral);
+                    body.emit("call mgol_copia");
+                }
+                _ => {
+                    body.emit(format!("movl {}, %eax", arg));
+                    body.emit(format!("movl %eax, {}", place));
+                }
+            }
+        }
+        body.code();
+        let mut lines = body.lines;
+        lines.push(format!("    .size {}, .-{}", symbol, symbol));
+        lines
+    }
+
+    fn write_file(&self, file: &mut File) -> io::Result<()> {
+        writeln!(file, "    .section .rodata")?;
+        writeln!(file, ".Lformato_inteiro:")?;
+        writeln!(file, "    .string \"%d\"")?;
+        writeln!(file, ".Lformato_real:")?;
+        writeln!(file, "    .string \"%lf\"")?;
+        writeln!(file, ".Lformato_literal:")?;
+        writeln!(file, "    .string \"%s\"")?;
+        writeln!(file, ".Lformato_leia_literal:")?;
+        writeln!(file, "    .string \"%255s\"")?;
+        writeln!(file, ".Lverdadeiro:")?;
+        writeln!(file, "    .string \"verdadeiro\"")?;
+        writeln!(file, ".Lfalso:")?;
+        writeln!(file, "    .string \"falso\"")?;
+        if self.helpers.contains(&Helper::IndexError) {
+            writeln!(file, ".Lerro_indice:")?;
+            writeln!(
+                file,
+                "    .string \"Erro: indice %d fora dos limites do vetor [0, %d) na linha %d\\n\""
+            )?;
+            writeln!(file, ".Lerro_indice_sem_linha:")?;
+            writeln!(file, "    .string \"Erro: indice %d fora dos limites do vetor [0, %d)\\n\"")?;
+        }
+        if self.helpers.contains(&Helper::DivisionError) {
+            writeln!(file, ".Lerro_divisao:")?;
+            writeln!(file, "    .string \"{}\"", asm_string("Erro: divisão por zero\n"))?;
+        }
+        for (n, literal) in self.literals.iter().enumerate() {
+            writeln!(file, ".LS{}:", n)?;
+            writeln!(file, "    .string \"{}\"", asm_string(literal))?;
+        }
+        if !self.reals.is_empty() {
+            writeln!(file, "    .align 8")?;
+        }
+        for (n, real) in self.reals.iter().enumerate() {
+            writeln!(file, ".LR{}:", n)?;
+            writeln!(file, "    .quad {:#x} # {:?}", real.to_bits(), real)?;
+        }
+        writeln!(file)?;
+
+        // the program variables
+        if !self.globals.is_empty() {
+            writeln!(file, "    .bss")?;
+            writeln!(file, "    .align 16")?;
+            for var in &self.globals {
+                writeln!(file, "v_{}:", var.name)?;
+                writeln!(file, "    .zero {}", var_size(var))?;
+            }
+            writeln!(file)?;
+        }
+
+        writeln!(file, "    .text")?;
+        for helper in &self.helpers {
+            let lines: &[&str] = match helper {
+                // copy the literal at %rsi to %rdi, cut to 255 bytes;
+                // it uses no stack, so a literal a function returned
+                // from its frame is still there
+                Helper::CopyLiteral => &[
+                    "mgol_copia:",
+                    "    xorl %ecx, %ecx",
+                    "1:",
+                    "    cmpl $255, %ecx",
+                    "    je 2f",
+                    "    movb (%rsi,%rcx), %al",
+                    "    testb %al, %al",
+                    "    je 2f",
+                    "    movb %al, (%rdi,%rcx)",
+                    "    incl %ecx",
+                    "    jmp 1b",
+                    "2:",
+                    "    movb $0, (%rdi,%rcx)",
+                    "    ret",
+                ],
+                // the index %edi is not in [0, %esi): the line is %edx,
+                // or 0 when the index is not checked
+                Helper::IndexError => &[
+                    "mgol_erro_indice:",
+                    "    subq $8, %rsp",
+                    "    movl %edx, %ecx",
+                    "    movl %esi, %edx",
+                    "    movl %edi, %esi",
+                    "    leaq .Lerro_indice(%rip), %rdi",
+                    "    testl %ecx, %ecx",
+                    "    jne 1f",
+                    "    leaq .Lerro_indice_sem_linha(%rip), %rdi",
+                    "1:",
+                    "    xorl %eax, %eax",
+                    "    call printf@PLT",
+                    "    movl $1, %edi",
+                    "    call exit@PLT",
+                ],
+                Helper::DivisionError => &[
+                    "mgol_erro_divisao:",
+                    "    subq $8, %rsp",
+                    "    leaq .Lerro_divisao(%rip), %rdi",
+                    "    xorl %eax, %eax",
+                    "    call printf@PLT",
+                    "    movl $1, %edi",
+                    "    call exit@PLT",
+                ],
+                // read the word verdadeiro or falso into the logico at %rdi
+                Helper::ReadLogico => &[
+                    "mgol_leia_logico:",
+                    "    pushq %rbx",
+                    "    subq $256, %rsp",
+                    "    movq %rdi, %rbx",
+                    "    leaq .Lformato_leia_literal(%rip), %rdi",
+                    "    movq %rsp, %rsi",
+                    "    xorl %eax, %eax",
+                    "    call scanf@PLT",
+                    "    cmpl $1, %eax",
+                    "    jne 1f",
+                    "    movq %rsp, %rdi",
+                    "    leaq .Lverdadeiro(%rip), %rsi",
+                    "    call strcmp@PLT",
+                    "    testl %eax, %eax",
+                    "    sete %al",
+                    "    movzbl %al, %eax",
+                    "    movl %eax, (%rbx)",
+                    "1:",
+                    "    addq $256, %rsp",
+                    "    popq %rbx",
+                    "    ret",
+                ],
+            };
+            for line in lines {
+                writeln!(file, "{}", line)?;
+            }
+            writeln!(file)?;
+        }
+
+        for function in &self.functions {
+            for line in function {
+                writeln!(file, "{}", line)?;
+            }
+            writeln!(file)?;
+        }
+        // the stack is not executable
+        writeln!(file, "    .section .note.GNU-stack,\"\",@progbits")
+    }
+
+    // write the assembly to path
+    pub fn create(&self, path: &str) {
+        let mut file = match File::create(path) {
+            Err(_) => panic!("Não foi possível criar o código de montagem {}", path),
+            Ok(file) => file,
+        };
+
+        if self.write_file(&mut file).is_err() {
+            panic!("Não foi possível escrever o código de montagem {}", path);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// The code generation interface used by the semantic analysis. A
+// Backend gets the program one declaration or statement at a time,
+// in the order they appear in the source code, and builds the
+// code of the program from them.
+//
+// The operands are the ones of the intermediate representation: a
+// variable, a temporary variable returned by new_temp, a constant (a
+// logico is 1 or 0), a literal (with the MGol escape sequences) or an
+// array element returned by index.
+use super::ir::{Operand, Operator, Type};
+
+pub trait Backend {
+    // declare a variable of the program or of the current subprogram;
+    // an array has a size
+    fn declare_variable(&mut self, name: &str, tk_type: Type, size: Option<usize>);
+
+    // all the variables of the program or of the subprogram were declared
+    fn end_declarations(&mut self);
+
+    // create a temporary variable of the given type
+    fn new_temp(&mut self, tk_type: Type) -> Operand;
+
+    // the operand for the element of an array
+    fn index(&mut self, array: &str, index: Operand) -> Operand;
+
+    // the same as index, but the index is checked at runtime and the
+    // program stops when it is not in [0, size)
+    fn checked_index(&mut self, array: &str, index: Operand, size: usize, row: usize) -> Operand;
+
+    // read a value from the standard input into target
+    fn read(&mut self, target: Operand, tk_type: Type);
+
+    // write the (operand, type) pairs to the standard output, one
+    // after the other
+    fn write(&mut self, args: Vec<(Operand, Type)>);
+
+    fn assign(&mut self, target: Operand, value: Operand, tk_type: Type);
+
+    // target = left operator right, for an arithmetic or a relational operator
+    fn binary_op(&mut self, target: Operand, left: Operand, operator: Operator, right: Operand);
+
+    // call a subprogram, keeping what it returns in target
+    fn call(&mut self, target: Option<Operand>, name: &str, args: Vec<Operand>);
+
+    // se (condition) entao ... senao ... fimse
+    fn if_begin(&mut self, condition: Operand);
+    fn else_begin(&mut self);
+    fn if_end(&mut self);
+
+    // a loop that only ends through loop_exit_unless
+    fn loop_begin(&mut self);
+    fn loop_exit_unless(&mut self, condition: Operand);
+    fn loop_end(&mut self);
+
+    // para var de start ate end passo step ... fimpara, where a
+    // negative step counts down
+    fn for_begin(&mut self, var: Operand, start: Operand, end: Operand, step: Operand);
+    fn for_end(&mut self);
+
+    // the (name, type) of the parameters are in params and a
+    // procedure has no return type
+    fn subprogram_begin(&mut self, name: &str, return_type: Option<Type>, params: &[(String, Type)]);
+    fn return_value(&mut self, value: Option<Operand>);
+    fn subprogram_end(&mut self);
+}
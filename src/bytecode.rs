@@ -0,0 +1,821 @@
+// The mgol bytecode: the instructions of a stack machine over typed
+// slots, compiled from the intermediate code and kept in .mgb files,
+// so a program can run many times without being analysed again.
+//
+// Each subprogram has its own slots: the parameters, then the local
+// variables and then the temporary variables. The program variables
+// are the global slots. An array is a single slot.
+use std::collections::HashMap;
+use std::fmt;
+use super::interpreter::{cut_literal, unescape};
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// the start of every .mgb file, followed by the version of the format
+pub const MAGIC: &[u8; 4] = b"MGB\0";
+pub const VERSION: u16 = 1;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Op {
+    // push a constant; a literal one is in the literal table
+    PushInt(i32),
+    PushReal(f64),
+    PushLit(u32),
+    // push or pop a slot of the subprogram or a global one
+    Load(u16),
+    Store(u16),
+    LoadGlobal(u16),
+    StoreGlobal(u16),
+    // the same for an array element, whose index is below the value
+    LoadElem(u16),
+    StoreElem(u16),
+    LoadGlobalElem(u16),
+    StoreGlobalElem(u16),
+    IntToReal,
+    RealToInt,
+    Pop,
+    // an operator on two inteiros (or logicos) or on two reals
+    BinInt(Operator),
+    BinReal(Operator),
+    // go to an instruction of the subprogram
+    Jump(u32),
+    JumpIfFalse(u32),
+    // replace the value on top with one read from the input, unless
+    // there is none
+    Read(Type),
+    Write(Type),
+    // call a subprogram with the arguments on top of the stack
+    Call(u16),
+    Return,
+    ReturnValue,
+    // stop the program when the index on top is not in [0, size); the
+    // index is removed
+    CheckIndex(u32, u32),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FunctionCode {
+    pub name: String,
+    pub return_type: Option<Type>,
+    // the first slots are the parameters
+    pub params: usize,
+    pub slots: Vec<Var>,
+    pub code: Vec<Op>,
+}
+
+// a compiled program; the main program is the last function
+#[derive(Clone, PartialEq, Debug)]
+pub struct Module {
+    pub globals: Vec<Var>,
+    pub literals: Vec<String>,
+    pub functions: Vec<FunctionCode>,
+}
+
+// where a name of the intermediate code is kept
+#[derive(Clone, Copy)]
+enum Place {
+    Local(u16),
+    Global(u16),
+}
+
+struct Compiler<'a> {
+    program: &'a Program,
+    function: &'a Function,
+    slots: HashMap<String, u16>,
+    literals: &'a mut Vec<String>,
+    code: Vec<Op>,
+}
+
+impl<'a> Compiler<'a> {
+    fn place(&self, name: &str) -> Place {
+        match self.slots.get(name) {
+            Some(slot) => Place::Local(*slot),
+            None => match self.program.globals.iter().position(|var| var.name == name) {
+                Some(slot) => Place::Global(slot as u16),
+                None => panic!("Variável '{}' não declarada no código intermediário", name),
+            },
+        }
+    }
+
+    // the temporary variables come after the parameters and the local variables
+    fn temp_slot(&self, n: usize) -> u16 {
+        (self.function.params.len() + self.function.locals.len() + n) as u16
+    }
+
+    fn literal(&mut self, text: String) -> u32 {
+        match self.literals.iter().position(|literal| *literal == text) {
+            Some(n) => n as u32,
+            None => {
+                self.literals.push(text);
+                self.literals.len() as u32 - 1
+            }
+        }
+    }
+
+    fn convert(&mut self, from: Type, to: Type) {
+        match (from, to) {
+            (Type::Inteiro, Type::Real) => self.code.push(Op::IntToReal),
+            (Type::Real, Type::Inteiro | Type::Logico) => self.code.push(Op::RealToInt),
+            _ => (),
+        }
+    }
+
+    // push the value of an operand, as a value of type tk_type
+    fn load(&mut self, operand: &Operand, tk_type: Type) {
+        match operand {
+            Operand::Int(n) => self.code.push(Op::PushInt(*n)),
+            Operand::Real(x) => self.code.push(Op::PushReal(*x)),
+            Operand::Lit(text) => {
+                let n = self.literal(unescape(text));
+                self.code.push(Op::PushLit(n));
+            }
+            Operand::Var(name) => match self.place(name) {
+                Place::Local(slot) => self.code.push(Op::Load(slot)),
+                Place::Global(slot) => self.code.push(Op::LoadGlobal(slot)),
+            },
+            Operand::Temp(n) => self.code.push(Op::Load(self.temp_slot(*n))),
+            Operand::Index(array, index) => {
+                self.load(index, Type::Inteiro);
+                match self.place(array) {
+                    Place::Local(slot) => self.code.push(Op::LoadElem(slot)),
+                    Place::Global(slot) => self.code.push(Op::LoadGlobalElem(slot)),
+                }
+            }
+        }
+        self.convert(self.program.type_of(self.function, operand), tk_type);
+    }
+
+    // pop a value of type tk_type into target; the index of an array
+    // element must be pushed before the value, with push_index
+    fn store(&mut self, target: &Operand, tk_type: Type) {
+        self.convert(tk_type, self.program.type_of(self.function, target));
+        match target {
+            Operand::Var(name) => match self.place(name) {
+                Place::Local(slot) => self.code.push(Op::Store(slot)),
+                Place::Global(slot) => self.code.push(Op::StoreGlobal(slot)),
+            },
+            Operand::Temp(n) => self.code.push(Op::Store(self.temp_slot(*n))),
+            Operand::Index(array, _) => match self.place(array) {
+                Place::Local(slot) => self.code.push(Op::StoreElem(slot)),
+                Place::Global(slot) => self.code.push(Op::StoreGlobalElem(slot)),
+            },
+            _ => panic!("Uma constante foi usada como destino no código intermediário"),
+        }
+    }
+
+    fn push_index(&mut self, target: &Operand) {
+        if let Operand::Index(_, index) = target {
+            self.load(index, Type::Inteiro);
+        }
+    }
+
+    fn compile(&mut self) {
+        // the jumps get the position of their labels at the end
+        let mut labels = HashMap::new();
+        let mut jumps = Vec::new();
+        let (program, function) = (self.program, self.function);
+        let type_of = |operand: &Operand| program.type_of(function, operand);
+
+        for instr in &function.code {
+            match instr {
+                Instr::Assign(target, Operand::Lit(text), Type::Literal) => {
+                    // a literal constant is cut like a literal variable is
+                    self.push_index(target);
+                    let n = self.literal(cut_literal(unescape(text)));
+                    self.code.push(Op::PushLit(n));
+                    self.store(target, Type::Literal);
+                }
+                Instr::Assign(target, value, _) => {
+                    let value_type = type_of(value);
+                    self.push_index(target);
+                    self.load(value, value_type);
+                    self.store(target, value_type);
+                }
+                Instr::BinOp(target, left, operator, right, tk_type) => {
+                    self.push_index(target);
+                    self.load(left, *tk_type);
+                    self.load(right, *tk_type);
+                    let result = if operator.is_relational() {
+                        Type::Logico
+                    } else {
+                        *tk_type
+                    };
+                    self.code.push(match tk_type {
+                        Type::Real => Op::BinReal(*operator),
+                        _ => Op::BinInt(*operator),
+                    });
+                    self.store(target, result);
+                }
+                Instr::Read(target, tk_type) => {
+                    self.push_index(target);
+                    self.load(target, *tk_type);
+                    self.code.push(Op::Read(*tk_type));
+                    self.store(target, *tk_type);
+                }
+                Instr::Write(value, tk_type) => {
+                    self.load(value, *tk_type);
+                    self.code.push(Op::Write(*tk_type));
+                }
+                Instr::Label(label) => {
+                    labels.insert(*label, self.code.len() as u32);
+                }
+                Instr::Goto(label) => {
+                    jumps.push((self.code.len(), *label));
+                    self.code.push(Op::Jump(0));
+                }
+                Instr::IfFalse(condition, label) => {
+                    self.load(condition, Type::Logico);
+                    jumps.push((self.code.len(), *label));
+                    self.code.push(Op::JumpIfFalse(0));
+                }
+                Instr::Call(target, name, args) => {
+                    let n = self
+                        .program
+                        .subprograms
+                        .iter()
+                        .position(|function| function.name == *name)
+                        .unwrap_or_else(|| panic!("Subprograma '{}' não definido no código intermediário", name));
+                    let callee = &self.program.subprograms[n];
+                    // the index of the target goes below the value returned
+                    if let Some(target) = target {
+                        self.push_index(target);
+                    }
+                    for (arg, param) in args.iter().zip(&callee.params) {
+                        self.load(arg, param.tk_type);
+                    }
+                    self.code.push(Op::Call(n as u16));
+                    match (target, callee.return_type) {
+                        (Some(target), Some(return_type)) => self.store(target, return_type),
+                        (None, Some(_)) => self.code.push(Op::Pop),
+                        _ => (),
+                    }
+                }
+                Instr::Return(Some(value)) => {
+                    self.load(value, self.function.return_type.unwrap_or(Type::Inteiro));
+                    self.code.push(Op::ReturnValue);
+                }
+                Instr::Return(None) => self.code.push(Op::Return),
+                Instr::CheckIndex(index, size, row) => {
+                    self.load(index, Type::Inteiro);
+                    self.code.push(Op::CheckIndex(*size as u32, *row as u32));
+                }
+            }
+        }
+        self.code.push(Op::Return);
+
+        for (position, label) in jumps {
+            let target = labels[&label];
+            self.code[position] = match self.code[position] {
+                Op::JumpIfFalse(_) => Op::JumpIfFalse(target),
+                _ => Op::Jump(target),
+            };
+        }
+    }
+}
+
+impl Module {
+    // compile the intermediate code of a program
+    pub fn compile(program: &Program) -> Module {
+        let mut literals = Vec::new();
+        let mut functions = Vec::new();
+
+        for function in program.subprograms.iter().chain(Some(&program.main)) {
+            let mut slots: Vec<Var> = function.params.iter().chain(&function.locals).cloned().collect();
+            for (n, tk_type) in function.temps.iter().enumerate() {
+                slots.push(Var {
+                    name: format!("T{}", n),
+                    tk_type: *tk_type,
+                    size: None,
+                });
+            }
+
+            let mut compiler = Compiler {
+                program,
+                function,
+                slots: function
+                    .params
+                    .iter()
+                    .chain(&function.locals)
+                    .enumerate()
+                    .map(|(n, var)| (var.name.clone(), n as u16))
+                    .collect(),
+                literals: &mut literals,
+                code: Vec::new(),
+            };
+            compiler.compile();
+            let code = compiler.code;
+
+            functions.push(FunctionCode {
+                name: function.name.clone(),
+                return_type: function.return_type,
+                params: function.params.len(),
+                slots,
+                code,
+            });
+        }
+
+        Module {
+            globals: program.globals.clone(),
+            literals,
+            functions,
+        }
+    }
+}
+
+// the operators and the types in the order of their codes in a .mgb file
+const OPERATORS: [Operator; 12] = [
+    Operator::Add,
+    Operator::Sub,
+    Operator::Mul,
+    Operator::Div,
+    Operator::Lt,
+    Operator::Le,
+    Operator::Gt,
+    Operator::Ge,
+    Operator::Eq,
+    Operator::Ne,
+    Operator::And,
+    Operator::Or,
+];
+const TYPES: [Type; 4] = [Type::Inteiro, Type::Real, Type::Literal, Type::Logico];
+// the code of a procedure's return type
+const NO_TYPE: u8 = 0xff;
+
+// The .mgb file is little-endian: a string is its length (u32) and its
+// UTF-8 bytes and an array size of 0 is a variable that is not an array.
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, n: u8) {
+        self.bytes.push(n);
+    }
+
+    fn u16(&mut self, n: u16) {
+        self.bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn string(&mut self, s: &str) {
+        self.u32(s.len() as u32);
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    fn tk_type(&mut self, tk_type: Option<Type>) {
+        match tk_type {
+            Some(tk_type) => self.u8(TYPES.iter().position(|t| *t == tk_type).unwrap() as u8),
+            None => self.u8(NO_TYPE),
+        }
+    }
+
+    fn var(&mut self, var: &Var) {
+        self.string(&var.name);
+        self.tk_type(Some(var.tk_type));
+        self.u32(var.size.unwrap_or(0) as u32);
+    }
+
+    fn op(&mut self, op: &Op) {
+        let operator_code = |operator: &Operator| OPERATORS.iter().position(|o| o == operator).unwrap() as u8;
+        match op {
+            Op::PushInt(n) => {
+                self.u8(0x01);
+                self.bytes.extend_from_slice(&n.to_le_bytes());
+            }
+            Op::PushReal(x) => {
+                self.u8(0x02);
+                self.bytes.extend_from_slice(&x.to_le_bytes());
+            }
+            Op::PushLit(n) => {
+                self.u8(0x03);
+                self.u32(*n);
+            }
+            Op::Load(slot) | Op::Store(slot) | Op::LoadGlobal(slot) | Op::StoreGlobal(slot) => {
+                self.u8(match op {
+                    Op::Load(_) => 0x04,
+                    Op::Store(_) => 0x05,
+                    Op::LoadGlobal(_) => 0x06,
+                    _ => 0x07,
+                });
+                self.u16(*slot);
+            }
+            Op::LoadElem(slot) | Op::StoreElem(slot) | Op::LoadGlobalElem(slot) | Op::StoreGlobalElem(slot) => {
+                self.u8(match op {
+                    Op::LoadElem(_) => 0x08,
+                    Op::StoreElem(_) => 0x09,
+                    Op::LoadGlobalElem(_) => 0x0a,
+                    _ => 0x0b,
+                });
+                self.u16(*slot);
+            }
+            Op::IntToReal => self.u8(0x0c),
+            Op::RealToInt => self.u8(0x0d),
+            Op::Pop => self.u8(0x0e),
+            Op::BinInt(operator) => {
+                self.u8(0x10);
+                self.u8(operator_code(operator));
+            }
+            Op::BinReal(operator) => {
+                self.u8(0x11);
+                self.u8(operator_code(operator));
+            }
+            Op::Jump(target) => {
+                self.u8(0x20);
+                self.u32(*target);
+            }
+            Op::JumpIfFalse(target) => {
+                self.u8(0x21);
+                self.u32(*target);
+            }
+            Op::Read(tk_type) => {
+                self.u8(0x30);
+                self.tk_type(Some(*tk_type));
+            }
+            Op::Write(tk_type) => {
+                self.u8(0x31);
+                self.tk_type(Some(*tk_type));
+            }
+            Op::Call(n) => {
+                self.u8(0x40);
+                self.u16(*n);
+            }
+            Op::Return => self.u8(0x41),
+            Op::ReturnValue => self.u8(0x42),
+            Op::CheckIndex(size, row) => {
+                self.u8(0x50);
+                self.u32(*size);
+                self.u32(*row);
+            }
+        }
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, n: usize) -> Result<&'b [u8], String> {
+        if self.bytes.len() < n {
+            return Err(String::from("o arquivo termina antes do esperado"));
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let n = self.u32()? as usize;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|_| String::from("texto que não está em UTF-8"))
+    }
+
+    fn tk_type(&mut self) -> Result<Option<Type>, String> {
+        match self.u8()? {
+            NO_TYPE => Ok(None),
+            code => match TYPES.get(code as usize) {
+                Some(tk_type) => Ok(Some(*tk_type)),
+                None => Err(format!("tipo desconhecido {}", code)),
+            },
+        }
+    }
+
+    fn var(&mut self) -> Result<Var, String> {
+        let name = self.string()?;
+        let tk_type = self.tk_type()?.ok_or_else(|| format!("a variável '{}' não tem tipo", name))?;
+        let size = Some(self.u32()? as usize).filter(|size| *size > 0);
+        Ok(Var { name, tk_type, size })
+    }
+
+    fn op(&mut self) -> Result<Op, String> {
+        let operator = |code: u8| {
+            OPERATORS
+                .get(code as usize)
+                .copied()
+                .ok_or_else(|| format!("operador desconhecido {}", code))
+        };
+        let tk_type = |tk_type: Option<Type>| tk_type.ok_or_else(|| String::from("instrução sem tipo"));
+        Ok(match self.u8()? {
+            0x01 => Op::PushInt(i32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            0x02 => Op::PushReal(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            0x03 => Op::PushLit(self.u32()?),
+            0x04 => Op::Load(self.u16()?),
+            0x05 => Op::Store(self.u16()?),
+            0x06 => Op::LoadGlobal(self.u16()?),
+            0x07 => Op::StoreGlobal(self.u16()?),
+            0x08 => Op::LoadElem(self.u16()?),
+            0x09 => Op::StoreElem(self.u16()?),
+            0x0a => Op::LoadGlobalElem(self.u16()?),
+            0x0b => Op::StoreGlobalElem(self.u16()?),
+            0x0c => Op::IntToReal,
+            0x0d => Op::RealToInt,
+            0x0e => Op::Pop,
+            0x10 => Op::BinInt(operator(self.u8()?)?),
+            0x11 => Op::BinReal(operator(self.u8()?)?),
+            0x20 => Op::Jump(self.u32()?),
+            0x21 => Op::JumpIfFalse(self.u32()?),
+            0x30 => Op::Read(tk_type(self.tk_type()?)?),
+            0x31 => Op::Write(tk_type(self.tk_type()?)?),
+            0x40 => Op::Call(self.u16()?),
+            0x41 => Op::Return,
+            0x42 => Op::ReturnValue,
+            0x50 => Op::CheckIndex(self.u32()?, self.u32()?),
+            code => return Err(format!("instrução desconhecida 0x{:02x}", code)),
+        })
+    }
+}
+
+impl Module {
+    // the contents of the .mgb file of the module
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u16(VERSION);
+
+        writer.u16(self.globals.len() as u16);
+        for var in &self.globals {
+            writer.var(var);
+        }
+        writer.u32(self.literals.len() as u32);
+        for literal in &self.literals {
+            writer.string(literal);
+        }
+        writer.u16(self.functions.len() as u16);
+        for function in &self.functions {
+            writer.string(&function.name);
+            writer.tk_type(function.return_type);
+            writer.u16(function.params as u16);
+            writer.u16(function.slots.len() as u16);
+            for var in &function.slots {
+                writer.var(var);
+            }
+            writer.u32(function.code.len() as u32);
+            for op in &function.code {
+                writer.op(op);
+            }
+        }
+
+        writer.bytes
+    }
+
+    // Read a module from the contents of a .mgb file. Whatever an
+    // instruction refers to must be in the module, so a module read
+    // with no error can be run.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Module, String> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(String::from("não é um arquivo .mgb"));
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(format!("a versão {} do formato não é suportada (a atual é {})", version, VERSION));
+        }
+
+        let mut globals = Vec::new();
+        for _ in 0..reader.u16()? {
+            globals.push(reader.var()?);
+        }
+        let mut literals = Vec::new();
+        for _ in 0..reader.u32()? {
+            literals.push(reader.string()?);
+        }
+        let mut functions = Vec::new();
+        for _ in 0..reader.u16()? {
+            let name = reader.string()?;
+            let return_type = reader.tk_type()?;
+            let params = reader.u16()? as usize;
+            let mut slots = Vec::new();
+            for _ in 0..reader.u16()? {
+                slots.push(reader.var()?);
+            }
+            let mut code = Vec::new();
+            for _ in 0..reader.u32()? {
+                code.push(reader.op()?);
+            }
+            functions.push(FunctionCode {
+                name,
+                return_type,
+                params,
+                slots,
+                code,
+            });
+        }
+        if !reader.bytes.is_empty() {
+            return Err(String::from("há bytes depois do fim do programa"));
+        }
+
+        let module = Module {
+            globals,
+            literals,
+            functions,
+        };
+        module.check()?;
+        Ok(module)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.functions.is_empty() {
+            return Err(String::from("o programa não tem código"));
+        }
+        if self.functions[self.functions.len() - 1].params > 0 {
+            return Err(String::from("o programa principal tem parâmetros"));
+        }
+        for function in &self.functions {
+            if function.params > function.slots.len() {
+                return Err(format!("'{}' tem mais parâmetros do que variáveis", function.name));
+            }
+            // an argument is a single value, never an array
+            if function.slots[..function.params].iter().any(|var| var.size.is_some()) {
+                return Err(format!("'{}' tem um vetor como parâmetro", function.name));
+            }
+            for (i, op) in function.code.iter().enumerate() {
+                let valid = match op {
+                    Op::PushLit(n) => (*n as usize) < self.literals.len(),
+                    // a whole array is never a value on the stack
+                    Op::Load(slot) | Op::Store(slot) => {
+                        function.slots.get(*slot as usize).is_some_and(|var| var.size.is_none())
+                    }
+                    Op::LoadElem(slot) | Op::StoreElem(slot) => {
+                        function.slots.get(*slot as usize).is_some_and(|var| var.size.is_some())
+                    }
+                    Op::LoadGlobal(slot) | Op::StoreGlobal(slot) => {
+                        self.globals.get(*slot as usize).is_some_and(|var| var.size.is_none())
+                    }
+                    Op::LoadGlobalElem(slot) | Op::StoreGlobalElem(slot) => {
+                        self.globals.get(*slot as usize).is_some_and(|var| var.size.is_some())
+                    }
+                    Op::Jump(target) | Op::JumpIfFalse(target) => (*target as usize) < function.code.len(),
+                    // the main program is not a subprogram
+                    Op::Call(n) => (*n as usize) + 1 < self.functions.len(),
+                    Op::ReturnValue => function.return_type.is_some(),
+                    _ => true,
+                };
+                if !valid {
+                    return Err(format!("instrução {} inválida em '{}': {}", i, function.name, op));
+                }
+            }
+            self.check_stack(function)?;
+        }
+        Ok(())
+    }
+
+    // how many values an instruction takes from the stack and how many
+    // it puts there
+    fn stack_effect(&self, op: &Op) -> (usize, usize) {
+        match op {
+            Op::PushInt(_) | Op::PushReal(_) | Op::PushLit(_) | Op::Load(_) | Op::LoadGlobal(_) => (0, 1),
+            Op::Store(_) | Op::StoreGlobal(_) | Op::Pop | Op::Write(_) | Op::CheckIndex(..) => (1, 0),
+            Op::LoadElem(_) | Op::LoadGlobalElem(_) | Op::IntToReal | Op::RealToInt | Op::Read(_) => (1, 1),
+            Op::StoreElem(_) | Op::StoreGlobalElem(_) => (2, 0),
+            Op::BinInt(_) | Op::BinReal(_) => (2, 1),
+            Op::Jump(_) | Op::Return => (0, 0),
+            Op::JumpIfFalse(_) | Op::ReturnValue => (1, 0),
+            Op::Call(n) => {
+                let callee = &self.functions[*n as usize];
+                (callee.params, callee.return_type.is_some() as usize)
+            }
+        }
+    }
+
+    // The stack of a function must have the same number of values at
+    // an instruction whatever the way the function got there, and
+    // enough values for each instruction, so the VM never takes a value
+    // the function did not put there.
+    fn check_stack(&self, function: &FunctionCode) -> Result<(), String> {
+        let mut depths: Vec<Option<usize>> = vec![None; function.code.len()];
+        let mut pending = vec![(0, 0)];
+        while let Some((i, depth)) = pending.pop() {
+            if i >= function.code.len() {
+                continue;
+            }
+            match depths[i] {
+                Some(known) if known == depth => continue,
+                Some(_) => {
+                    return Err(format!(
+                        "a pilha chega à instrução {} de '{}' com alturas diferentes",
+                        i, function.name
+                    ))
+                }
+                None => depths[i] = Some(depth),
+            }
+
+            let op = &function.code[i];
+            let (pops, pushes) = self.stack_effect(op);
+            if depth < pops {
+                return Err(format!(
+                    "a instrução {} de '{}' tira mais valores da pilha do que ela tem: {}",
+                    i, function.name, op
+                ));
+            }
+            let depth = depth - pops + pushes;
+            match op {
+                Op::Jump(target) => pending.push((*target as usize, depth)),
+                Op::JumpIfFalse(target) => {
+                    pending.push((*target as usize, depth));
+                    pending.push((i + 1, depth));
+                }
+                Op::Return | Op::ReturnValue => (),
+                _ => pending.push((i + 1, depth)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::PushInt(n) => write!(f, "push_int {}", n),
+            Op::PushReal(x) => write!(f, "push_real {:?}", x),
+            Op::PushLit(n) => write!(f, "push_lit {}", n),
+            Op::Load(slot) => write!(f, "load {}", slot),
+            Op::Store(slot) => write!(f, "store {}", slot),
+            Op::LoadGlobal(slot) => write!(f, "load_global {}", slot),
+            Op::StoreGlobal(slot) => write!(f, "store_global {}", slot),
+            Op::LoadElem(slot) => write!(f, "load_elem {}", slot),
+            Op::StoreElem(slot) => write!(f, "store_elem {}", slot),
+            Op::LoadGlobalElem(slot) => write!(f, "load_global_elem {}", slot),
+            Op::StoreGlobalElem(slot) => write!(f, "store_global_elem {}", slot),
+            Op::IntToReal => write!(f, "int_to_real"),
+            Op::RealToInt => write!(f, "real_to_int"),
+            Op::Pop => write!(f, "pop"),
+            Op::BinInt(operator) => write!(f, "bin_int {}", operator.lexeme()),
+            Op::BinReal(operator) => write!(f, "bin_real {}", operator.lexeme()),
+            Op::Jump(target) => write!(f, "jump {}", target),
+            Op::JumpIfFalse(target) => write!(f, "jump_if_false {}", target),
+            Op::Read(tk_type) => write!(f, "read {}", tk_type),
+            Op::Write(tk_type) => write!(f, "write {}", tk_type),
+            Op::Call(n) => write!(f, "call {}", n),
+            Op::Return => write!(f, "return"),
+            Op::ReturnValue => write!(f, "return_value"),
+            Op::CheckIndex(size, row) => write!(f, "check_index {} {}", size, row),
+        }
+    }
+}
+
+fn write_slot(f: &mut fmt::Formatter, kind: &str, n: usize, var: &Var) -> fmt::Result {
+    match var.size {
+        Some(size) => writeln!(f, "    {} {}: {} {}[{}]", kind, n, var.tk_type, var.name, size),
+        None => writeln!(f, "    {} {}: {} {}", kind, n, var.tk_type, var.name),
+    }
+}
+
+// the disassembly of the module, with the names of what each
+// instruction refers to after a ';'
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "mgb versão {}", VERSION)?;
+        writeln!(f)?;
+        for (n, var) in self.globals.iter().enumerate() {
+            write_slot(f, "global", n, var)?;
+        }
+        for (n, literal) in self.literals.iter().enumerate() {
+            writeln!(f, "    literal {}: {:?}", n, literal)?;
+        }
+
+        for (n, function) in self.functions.iter().enumerate() {
+            writeln!(f)?;
+            match function.return_type {
+                _ if n + 1 == self.functions.len() => writeln!(f, "programa")?,
+                Some(return_type) => writeln!(f, "funcao {} {} ({})", n, function.name, return_type)?,
+                None => writeln!(f, "procedimento {} {}", n, function.name)?,
+            }
+            for (n, var) in function.slots.iter().enumerate() {
+                let kind = if n < function.params { "param" } else { "slot" };
+                write_slot(f, kind, n, var)?;
+            }
+            for (i, op) in function.code.iter().enumerate() {
+                let name = match op {
+                    Op::Load(slot) | Op::Store(slot) | Op::LoadElem(slot) | Op::StoreElem(slot) => {
+                        Some(function.slots[*slot as usize].name.clone())
+                    }
+                    Op::LoadGlobal(slot)
+                    | Op::StoreGlobal(slot)
+                    | Op::LoadGlobalElem(slot)
+                    | Op::StoreGlobalElem(slot) => Some(self.globals[*slot as usize].name.clone()),
+                    Op::PushLit(n) => Some(format!("{:?}", self.literals[*n as usize])),
+                    Op::Call(n) => Some(self.functions[*n as usize].name.clone()),
+                    _ => None,
+                };
+                match name {
+                    Some(name) => writeln!(f, "{:6}  {:24}; {}", i, op.to_string(), name)?,
+                    None => writeln!(f, "{:6}  {}", i, op)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+// The encodings a MGol source file may be saved with. Files
+// written on Windows editors are often Windows-1252 (or its
+// subset Latin-1) instead of UTF-8.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Latin1,
+    Cp1252,
+}
+
+// the characters of the 0x80..=0x9F range in Windows-1252; the five
+// bytes it leaves undefined are kept as the Latin-1 control characters
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+impl Encoding {
+    // the encoding given by its name in the command line
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        match name.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Encoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Some(Encoding::Latin1),
+            "cp1252" | "windows-1252" => Some(Encoding::Cp1252),
+            _ => None,
+        }
+    }
+
+    // Decode a line of the source file. When the bytes are not valid
+    // in this encoding (it only happens with UTF-8), the line is still
+    // decoded, with the invalid bytes replaced by U+FFFD, and the
+    // position (in characters) of the first invalid one is returned.
+    pub fn decode(&self, bytes: &[u8]) -> (String, Option<usize>) {
+        match self {
+            Encoding::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(s) => (String::from(s), None),
+                Err(e) => {
+                    let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap();
+                    (
+                        String::from_utf8_lossy(bytes).into_owned(),
+                        Some(valid.chars().count() + 1),
+                    )
+                }
+            },
+            Encoding::Latin1 => (bytes.iter().map(|&b| b as char).collect(), None),
+            Encoding::Cp1252 => (
+                bytes
+                    .iter()
+                    .map(|&b| match b {
+                        0x80..=0x9f => CP1252_HIGH[(b - 0x80) as usize],
+                        _ => b as char,
+                    })
+                    .collect(),
+                None,
+            ),
+        }
+    }
+}
@@ -28,6 +28,12 @@ pub struct Grammar {
     rules: Vec<GrammarRule>,
 }
 
+impl Default for Grammar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Grammar {
     pub fn new() -> Grammar {
         let mut grammar = Grammar { rules: vec![] };
@@ -60,32 +66,121 @@ impl Grammar {
         self.add_grammar_rule("TIPO", "literal");
         self.add_grammar_rule("A", "ES A");
         self.add_grammar_rule("ES", "leia id pt_v");
-        self.add_grammar_rule("ES", "escreva ARG pt_v");
+        self.add_grammar_rule("ES", "escreva LARG pt_v");
         self.add_grammar_rule("ARG", "lit");
-        self.add_grammar_rule("ARG", "num");
-        self.add_grammar_rule("ARG", "id");
+        self.add_grammar_rule("ARG", "LD");
+        self.add_grammar_rule("LARG", "ARG");
         self.add_grammar_rule("A", "CMD A");
         self.add_grammar_rule("CMD", "id rcb LD pt_v");
-        self.add_grammar_rule("LD", "OPRD opm OPRD");
-        self.add_grammar_rule("LD", "OPRD");
+        self.add_grammar_rule("LDN", "OPRD opm OPRD");
+        self.add_grammar_rule("LDN", "OPRD");
         self.add_grammar_rule("OPRD", "id");
         self.add_grammar_rule("OPRD", "num");
         self.add_grammar_rule("A", "COND A");
         self.add_grammar_rule("COND", "CAB CP");
         self.add_grammar_rule("CAB", "se ab_p EXP_R fc_p entao");
-        self.add_grammar_rule("EXP_R", "OPRD opr OPRD");
+        self.add_grammar_rule("EXP_R", "LD");
         self.add_grammar_rule("CP", "ES CP");
         self.add_grammar_rule("CP", "CMD CP");
         self.add_grammar_rule("CP", "COND CP");
         self.add_grammar_rule("CP", "fimse");
         self.add_grammar_rule("A", "R A");
         self.add_grammar_rule("R", "CABR CPR");
-        self.add_grammar_rule("CABR", "repita ab_p EXP_R fc_p");
+        self.add_grammar_rule("CABR", "REP ab_p EXP_R fc_p");
         self.add_grammar_rule("CPR", "ES CPR");
         self.add_grammar_rule("CPR", "CMD CPR");
         self.add_grammar_rule("CPR", "COND CPR");
         self.add_grammar_rule("CPR", "fimrepita");
         self.add_grammar_rule("A", "fim");
+        self.add_grammar_rule("CP", "CABS CPS");
+        self.add_grammar_rule("CABS", "senao");
+        self.add_grammar_rule("CPS", "ES CPS");
+        self.add_grammar_rule("CPS", "CMD CPS");
+        self.add_grammar_rule("CPS", "COND CPS");
+        self.add_grammar_rule("CPS", "fimse");
+        self.add_grammar_rule("LARG", "LARG vir ARG");
+        self.add_grammar_rule("A", "RE A");
+        self.add_grammar_rule("RE", "CABE CPE");
+        self.add_grammar_rule("CABE", "ENQ ab_p EXP_R fc_p");
+        self.add_grammar_rule("ENQ", "enquanto");
+        self.add_grammar_rule("CPE", "ES CPE");
+        self.add_grammar_rule("CPE", "CMD CPE");
+        self.add_grammar_rule("CPE", "COND CPE");
+        self.add_grammar_rule("CPE", "fimenquanto");
+        self.add_grammar_rule("A", "RP A");
+        self.add_grammar_rule("RP", "CABP CPP");
+        self.add_grammar_rule("CABP", "para id de OPRD ate OPRD passo OPRD");
+        self.add_grammar_rule("CABP", "para id de OPRD ate OPRD");
+        self.add_grammar_rule("CPP", "ES CPP");
+        self.add_grammar_rule("CPP", "CMD CPP");
+        self.add_grammar_rule("CPP", "COND CPP");
+        self.add_grammar_rule("CPP", "fimpara");
+        self.add_grammar_rule("A", "SUB A");
+        self.add_grammar_rule("SUB", "CABF V CPF");
+        self.add_grammar_rule("SUB", "CABF CPF");
+        self.add_grammar_rule("CABF", "NOMEF ab_p LPAR fc_p");
+        self.add_grammar_rule("CABF", "NOMEF ab_p fc_p");
+        self.add_grammar_rule("NOMEF", "funcao TIPO id");
+        self.add_grammar_rule("NOMEF", "procedimento id");
+        self.add_grammar_rule("LPAR", "PAR");
+        self.add_grammar_rule("LPAR", "LPAR vir PAR");
+        self.add_grammar_rule("PAR", "TIPO id");
+        self.add_grammar_rule("CPF", "ES CPF");
+        self.add_grammar_rule("CPF", "CMD CPF");
+        self.add_grammar_rule("CPF", "COND CPF");
+        self.add_grammar_rule("CPF", "R CPF");
+        self.add_grammar_rule("CPF", "RE CPF");
+        self.add_grammar_rule("CPF", "RP CPF");
+        self.add_grammar_rule("CPF", "RET CPF");
+        self.add_grammar_rule("CPF", "fimfuncao");
+        self.add_grammar_rule("CPF", "fimprocedimento");
+        self.add_grammar_rule("RET", "retorne LD pt_v");
+        self.add_grammar_rule("RET", "retorne pt_v");
+        self.add_grammar_rule("CP", "RET CP");
+        self.add_grammar_rule("CPS", "RET CPS");
+        self.add_grammar_rule("CPR", "RET CPR");
+        self.add_grammar_rule("CPE", "RET CPE");
+        self.add_grammar_rule("CPP", "RET CPP");
+        self.add_grammar_rule("CMD", "id ab_p LARGC fc_p pt_v");
+        self.add_grammar_rule("CMD", "id ab_p fc_p pt_v");
+        self.add_grammar_rule("OPRD", "id ab_p LARGC fc_p");
+        self.add_grammar_rule("OPRD", "id ab_p fc_p");
+        self.add_grammar_rule("LARGC", "LD");
+        self.add_grammar_rule("LARGC", "LARGC vir LD");
+        self.add_grammar_rule("A", "RET A");
+        self.add_grammar_rule("L", "id ab_c OPRD fc_c");
+        self.add_grammar_rule("ES", "leia id ab_c LD fc_c pt_v");
+        self.add_grammar_rule("CMD", "id ab_c LD fc_c rcb LD pt_v");
+        self.add_grammar_rule("OPRD", "id ab_c LD fc_c");
+        self.add_grammar_rule("TIPO", "logico");
+        self.add_grammar_rule("OPRD", "vlog");
+        self.add_grammar_rule("LDN", "OPRD opr OPRD");
+        self.add_grammar_rule("LDE", "LDE e LDN");
+        self.add_grammar_rule("D", "constante id rcb VCTE pt_v");
+        self.add_grammar_rule("VCTE", "num");
+        self.add_grammar_rule("VCTE", "lit");
+        self.add_grammar_rule("VCTE", "vlog");
+        self.add_grammar_rule("REP", "repita");
+        self.add_grammar_rule("CP", "RE CP");
+        self.add_grammar_rule("CP", "RP CP");
+        self.add_grammar_rule("CPS", "RE CPS");
+        self.add_grammar_rule("CPS", "RP CPS");
+        self.add_grammar_rule("CPR", "RE CPR");
+        self.add_grammar_rule("CPR", "RP CPR");
+        self.add_grammar_rule("CPE", "RE CPE");
+        self.add_grammar_rule("CPE", "RP CPE");
+        self.add_grammar_rule("CPP", "RE CPP");
+        self.add_grammar_rule("CPP", "RP CPP");
+        self.add_grammar_rule("CP", "R CP");
+        self.add_grammar_rule("CPS", "R CPS");
+        self.add_grammar_rule("CPR", "R CPR");
+        self.add_grammar_rule("CPE", "R CPE");
+        self.add_grammar_rule("CPP", "R CPP");
+        self.add_grammar_rule("CABP", "para id de OPRD ate OPRD passo opm OPRD");
+        self.add_grammar_rule("LD", "LD ou LDE");
+        self.add_grammar_rule("LD", "LDE");
+        self.add_grammar_rule("LDE", "LDN");
+        self.add_grammar_rule("LDN", "nao LDN");
     }
 
     fn add_grammar_rule(&mut self, left_str: &str, right_str: &str) {
@@ -0,0 +1,475 @@
+// Run a MGol program from its intermediate code, without a C
+// compiler. It reads and writes the same as the obj file of the
+// program: numbers are read and written like scanf and printf do with
+// %d and %lf, and a logico is the word verdadeiro or falso.
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// a literal has room for 255 bytes, like the C literal type
+const LITERAL_SIZE: usize = 255;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Value {
+    // an inteiro or a logico (1 or 0)
+    Int(i32),
+    Real(f64),
+    Lit(String),
+}
+
+pub(crate) enum Slot {
+    Scalar(Value),
+    Array(Vec<Value>),
+}
+
+// the value a variable has before it is written
+pub(crate) fn zero(tk_type: Type) -> Value {
+    match tk_type {
+        Type::Inteiro | Type::Logico => Value::Int(0),
+        Type::Real => Value::Real(0.0),
+        Type::Literal => Value::Lit(String::new()),
+    }
+}
+
+pub(crate) fn declare(var: &Var) -> Slot {
+    match var.size {
+        Some(size) => Slot::Array(vec![zero(var.tk_type); size]),
+        None => Slot::Scalar(zero(var.tk_type)),
+    }
+}
+
+// the text a literal variable keeps: its first LITERAL_SIZE bytes
+pub(crate) fn cut_literal(mut text: String) -> String {
+    if text.len() > LITERAL_SIZE {
+        let mut end = LITERAL_SIZE;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+// a value kept in a variable of the given type, as the C assignment does
+pub(crate) fn convert(value: Value, tk_type: Type) -> Value {
+    match (value, tk_type) {
+        (Value::Int(n), Type::Real) => Value::Real(n as f64),
+        (Value::Real(x), Type::Inteiro | Type::Logico) => Value::Int(x as i32),
+        (Value::Lit(text), _) => Value::Lit(cut_literal(text)),
+        (value, _) => value,
+    }
+}
+
+// the text of a literal constant, with its escape sequences replaced
+pub(crate) fn unescape(text: &str) -> String {
+    let mut s = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            s.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => s.push('\n'),
+            Some('t') => s.push('\t'),
+            Some(c) => s.push(c),
+            None => (),
+        }
+    }
+    s
+}
+
+fn number(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Real(x) => *x,
+        Value::Lit(_) => 0.0,
+    }
+}
+
+pub(crate) fn integer(value: &Value) -> i32 {
+    match value {
+        Value::Int(n) => *n,
+        Value::Real(x) => *x as i32,
+        Value::Lit(_) => 0,
+    }
+}
+
+// a value as the obj file writes it: a real like printf does with %lf
+// and a logico as the word verdadeiro or falso
+pub(crate) fn format(value: Value, tk_type: Type) -> String {
+    match (value, tk_type) {
+        (Value::Int(n), Type::Logico) => String::from(if n != 0 { "verdadeiro" } else { "falso" }),
+        (Value::Int(n), _) => n.to_string(),
+        (Value::Real(x), _) if x.is_nan() => String::from("nan"),
+        (Value::Real(x), _) => format!("{:.6}", x),
+        (Value::Lit(text), _) => text,
+    }
+}
+
+// left operator right, as real numbers when the type is real
+pub(crate) fn binary_op(
+    left: Value,
+    operator: Operator,
+    right: Value,
+    tk_type: Type,
+) -> Result<Value, String> {
+    if operator.is_relational() || tk_type == Type::Real {
+        let (left, right) = (number(&left), number(&right));
+        return Ok(match operator {
+            Operator::Add => Value::Real(left + right),
+            Operator::Sub => Value::Real(left - right),
+            Operator::Mul => Value::Real(left * right),
+            Operator::Div => Value::Real(left / right),
+            Operator::Lt => Value::Int((left < right) as i32),
+            Operator::Le => Value::Int((left <= right) as i32),
+            Operator::Gt => Value::Int((left > right) as i32),
+            Operator::Ge => Value::Int((left >= right) as i32),
+            Operator::Eq => Value::Int((left == right) as i32),
+            Operator::Ne => Value::Int((left != right) as i32),
+            Operator::And => Value::Int((left != 0.0 && right != 0.0) as i32),
+            Operator::Or => Value::Int((left != 0.0 || right != 0.0) as i32),
+        });
+    }
+
+    let (left, right) = (integer(&left), integer(&right));
+    Ok(Value::Int(match operator {
+        Operator::Add => left.wrapping_add(right),
+        Operator::Sub => left.wrapping_sub(right),
+        Operator::Mul => left.wrapping_mul(right),
+        _ if right == 0 => return Err(String::from("Erro: divisão por zero\n")),
+        _ => left.wrapping_div(right),
+    }))
+}
+
+// The standard input of a running program, read like scanf does.
+pub(crate) struct Input<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> Input<R> {
+    pub(crate) fn new(reader: R) -> Input<R> {
+        Input { reader }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        match self.reader.fill_buf() {
+            Ok(buf) if !buf.is_empty() => Some(buf[0]),
+            _ => None,
+        }
+    }
+
+    // the next input bytes for which accept is true
+    fn read_while(&mut self, mut accept: impl FnMut(u8, &[u8]) -> bool) -> String {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.reader.consume(1);
+        }
+        let mut bytes = Vec::new();
+        while let Some(c) = self.peek() {
+            if !accept(c, &bytes) {
+                break;
+            }
+            bytes.push(c);
+            self.reader.consume(1);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    // A value of the given type from the input, or None when there is
+    // none (the variable is then left as it is, as scanf does).
+    pub(crate) fn read(&mut self, tk_type: Type) -> Option<Value> {
+        match tk_type {
+            Type::Inteiro => {
+                let s = self.read_while(|c, read| {
+                    c.is_ascii_digit() || (read.is_empty() && (c == b'-' || c == b'+'))
+                });
+                s.parse::<i64>().ok().map(|n| Value::Int(n as i32))
+            }
+            Type::Real => {
+                let s = self.read_while(|c, read| match c {
+                    b'0'..=b'9' | b'.' => true,
+                    b'e' | b'E' => !read.is_empty(),
+                    b'-' | b'+' => read.is_empty() || matches!(read.last(), Some(b'e' | b'E')),
+                    _ => false,
+                });
+                // the longest start of it that is a number
+                (1..=s.len())
+                    .rev()
+                    .find_map(|end| s[..end].parse::<f64>().ok())
+                    .map(Value::Real)
+            }
+            Type::Literal => {
+                let s = self.read_while(|c, _| !c.is_ascii_whitespace());
+                if s.is_empty() {
+                    None
+                } else {
+                    Some(Value::Lit(s))
+                }
+            }
+            Type::Logico => {
+                let s = self.read_while(|c, _| !c.is_ascii_whitespace());
+                if s.is_empty() {
+                    None
+                } else {
+                    Some(Value::Int((s == "verdadeiro") as i32))
+                }
+            }
+        }
+    }
+}
+
+struct Interpreter<'a, R: BufRead, W: Write> {
+    program: &'a Program,
+    globals: HashMap<String, Slot>,
+    input: Input<R>,
+    output: W,
+}
+
+// how many subprogram calls may be running at once; a program that
+// goes deeper is stopped, as one that recurses forever would be
+pub(crate) const MAX_CALLS: usize = 200_000;
+
+pub(crate) fn too_many_calls() -> String {
+    format!("Erro: mais de {} chamadas de subprogramas em andamento\n", MAX_CALLS)
+}
+
+// the variables of a subprogram (or of the main program) while it
+// runs, with the instruction it is at and where the caller keeps what
+// it returns
+struct Frame<'a> {
+    function: &'a Function,
+    vars: HashMap<String, Slot>,
+    temps: Vec<Value>,
+    labels: HashMap<usize, usize>,
+    pc: usize,
+    target: Option<&'a Operand>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(function: &'a Function, args: Vec<Value>, target: Option<&'a Operand>) -> Frame<'a> {
+        let mut vars = HashMap::new();
+        for var in &function.locals {
+            vars.insert(var.name.clone(), declare(var));
+        }
+        for (param, arg) in function.params.iter().zip(args) {
+            vars.insert(param.name.clone(), Slot::Scalar(convert(arg, param.tk_type)));
+        }
+        let labels = function
+            .code
+            .iter()
+            .enumerate()
+            .filter_map(|(i, instr)| match instr {
+                Instr::Label(label) => Some((*label, i)),
+                _ => None,
+            })
+            .collect();
+
+        Frame {
+            function,
+            vars,
+            temps: function.temps.iter().map(|tk_type| zero(*tk_type)).collect(),
+            labels,
+            pc: 0,
+            target,
+        }
+    }
+}
+
+impl<'a, R: BufRead, W: Write> Interpreter<'a, R, W> {
+    fn slot<'f>(&'f mut self, frame: &'f mut Frame<'a>, name: &str) -> &'f mut Slot {
+        match frame.vars.get_mut(name) {
+            Some(slot) => slot,
+            None => self.globals.get_mut(name).unwrap_or_else(|| {
+                panic!("Variável '{}' não declarada no código intermediário", name)
+            }),
+        }
+    }
+
+    // the position of an array element, which must be in bounds
+    fn element(
+        &mut self,
+        frame: &mut Frame<'a>,
+        function: &Function,
+        array: &str,
+        index: &Operand,
+    ) -> Result<usize, String> {
+        let i = integer(&self.value(frame, function, index)?);
+        let size = match self.slot(frame, array) {
+            Slot::Array(values) => values.len(),
+            Slot::Scalar(_) => 0,
+        };
+        if i < 0 || i as usize >= size {
+            return Err(format!(
+                "Erro: indice {} fora dos limites do vetor [0, {})\n",
+                i, size
+            ));
+        }
+        Ok(i as usize)
+    }
+
+    fn value(
+        &mut self,
+        frame: &mut Frame<'a>,
+        function: &Function,
+        operand: &Operand,
+    ) -> Result<Value, String> {
+        Ok(match operand {
+            Operand::Int(n) => Value::Int(*n),
+            Operand::Real(x) => Value::Real(*x),
+            Operand::Lit(text) => Value::Lit(unescape(text)),
+            Operand::Var(name) => match self.slot(frame, name) {
+                Slot::Scalar(value) => value.clone(),
+                Slot::Array(_) => panic!(
+                    "O vetor '{}' foi usado como valor no código intermediário",
+                    name
+                ),
+            },
+            Operand::Temp(n) => frame.temps[*n].clone(),
+            Operand::Index(array, index) => {
+                let i = self.element(frame, function, array, index)?;
+                match self.slot(frame, array) {
+                    Slot::Array(values) => values[i].clone(),
+                    Slot::Scalar(_) => unreachable!(),
+                }
+            }
+        })
+    }
+
+    fn store(
+        &mut self,
+        frame: &mut Frame<'a>,
+        function: &Function,
+        target: &Operand,
+        value: Value,
+    ) -> Result<(), String> {
+        let value = convert(value, self.program.type_of(function, target));
+        match target {
+            Operand::Var(name) => *self.slot(frame, name) = Slot::Scalar(value),
+            Operand::Temp(n) => frame.temps[*n] = value,
+            Operand::Index(array, index) => {
+                let i = self.element(frame, function, array, index)?;
+                if let Slot::Array(values) = self.slot(frame, array) {
+                    values[i] = value;
+                }
+            }
+            _ => panic!("Uma constante foi usada como destino no código intermediário"),
+        }
+        Ok(())
+    }
+
+    // Run the main program. The subprograms it calls run in frames
+    // of their own on top of its frame, so a deep recursion takes
+    // memory but not the stack of the interpreter.
+    fn run_main(&mut self) -> Result<(), String> {
+        let program = self.program;
+        let mut frames = vec![Frame::new(&program.main, Vec::new(), None)];
+
+        while let Some(frame) = frames.last_mut() {
+            let function = frame.function;
+            // a subprogram that ends without 'retorne' returns nothing
+            let mut returned = None;
+            if let Some(instr) = function.code.get(frame.pc) {
+                frame.pc += 1;
+                match instr {
+                    Instr::Assign(target, value, _) => {
+                        let value = self.value(frame, function, value)?;
+                        self.store(frame, function, target, value)?;
+                    }
+                    Instr::BinOp(target, left, operator, right, tk_type) => {
+                        let left = self.value(frame, function, left)?;
+                        let right = self.value(frame, function, right)?;
+                        let value = binary_op(left, *operator, right, *tk_type)?;
+                        self.store(frame, function, target, value)?;
+                    }
+                    Instr::Read(target, tk_type) => {
+                        // the output so far is seen before the program waits
+                        let _ = self.output.flush();
+                        if let Some(value) = self.input.read(*tk_type) {
+                            self.store(frame, function, target, value)?;
+                        }
+                    }
+                    Instr::Write(value, tk_type) => {
+                        let text = format(self.value(frame, function, value)?, *tk_type);
+                        let _ = self.output.write_all(text.as_bytes());
+                    }
+                    Instr::Label(_) => (),
+                    Instr::Goto(label) => frame.pc = frame.labels[label],
+                    Instr::IfFalse(condition, label) => {
+                        if integer(&self.value(frame, function, condition)?) == 0 {
+                            frame.pc = frame.labels[label];
+                        }
+                    }
+                    Instr::Call(target, name, args) => {
+                        let mut values = Vec::new();
+                        for arg in args {
+                            values.push(self.value(frame, function, arg)?);
+                        }
+                        let callee = program.get_function(name).unwrap_or_else(|| {
+                            panic!(
+                                "Subprograma '{}' não definido no código intermediário",
+                                name
+                            )
+                        });
+                        if frames.len() == MAX_CALLS {
+                            return Err(too_many_calls());
+                        }
+                        frames.push(Frame::new(callee, values, target.as_ref()));
+                        continue;
+                    }
+                    Instr::Return(value) => {
+                        if let Some(value) = value {
+                            returned = Some(self.value(frame, function, value)?);
+                        }
+                        frame.pc = function.code.len();
+                    }
+                    Instr::CheckIndex(index, size, row) => {
+                        let i = integer(&self.value(frame, function, index)?);
+                        if i < 0 || i as usize >= *size {
+                            return Err(format!(
+                                "Erro: indice {} fora dos limites do vetor [0, {}) na linha {}\n",
+                                i, size, row
+                            ));
+                        }
+                    }
+                }
+                if frame.pc < function.code.len() {
+                    continue;
+                }
+            }
+
+            // the frame is done: what it returned goes to its caller
+            let target = frames.pop().and_then(|frame| frame.target);
+            if let (Some(caller), Some(target), Some(value)) = (frames.last_mut(), target, returned) {
+                let caller_function = caller.function;
+                self.store(caller, caller_function, target, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Run the program, reading from input and writing to output, and
+// return its exit status: 1 when it stopped because of an error,
+// whose message is written like the obj file does.
+pub fn run(program: &Program, input: impl BufRead, output: impl Write) -> i32 {
+    let mut interpreter = Interpreter {
+        program,
+        globals: program
+            .globals
+            .iter()
+            .map(|var| (var.name.clone(), declare(var)))
+            .collect(),
+        input: Input::new(input),
+        output,
+    };
+
+    let status = match interpreter.run_main() {
+        Ok(_) => 0,
+        Err(msg) => {
+            let _ = interpreter.output.write_all(msg.as_bytes());
+            1
+        }
+    };
+    let _ = interpreter.output.flush();
+    status
+}
@@ -0,0 +1,457 @@
+// The intermediate representation of a MGol program: three-address
+// code (TAC), where each instruction has at most one operator. It is
+// built by the IrBuilder from what the semantic analysis finds and
+// every target code is generated from it.
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Type {
+    Inteiro,
+    Real,
+    Literal,
+    Logico,
+}
+
+impl Type {
+    // the Type of a MGol type name
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "inteiro" => Some(Type::Inteiro),
+            "real" => Some(Type::Real),
+            "literal" => Some(Type::Literal),
+            "logico" => Some(Type::Logico),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Inteiro => "inteiro",
+            Type::Real => "real",
+            Type::Literal => "literal",
+            Type::Logico => "logico",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operand {
+    // a variable or a parameter
+    Var(String),
+    // the temporary variable Tn of the function
+    Temp(usize),
+    // an inteiro constant; a logico is 1 (verdadeiro) or 0 (falso)
+    Int(i32),
+    Real(f64),
+    // a literal constant, without the quotes and with the MGol escape
+    // sequences as they are in the source code
+    Lit(String),
+    // the element of an array
+    Index(String, Box<Operand>),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Var(name) => write!(f, "{}", name),
+            Operand::Temp(n) => write!(f, "T{}", n),
+            Operand::Int(n) => write!(f, "{}", n),
+            Operand::Real(x) => write!(f, "{:?}", x),
+            Operand::Lit(text) => write!(f, "\"{}\"", text),
+            Operand::Index(array, index) => write!(f, "{}[{}]", array, index),
+        }
+    }
+}
+
+impl Operand {
+    // the name of the variable, when the operand is a variable
+    pub fn var_name(&self) -> Option<&str> {
+        match self {
+            Operand::Var(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    // the number of the temporary variable, when the operand is one
+    pub fn temp(&self) -> Option<usize> {
+        match self {
+            Operand::Temp(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+// the places where an operand read as a value has variables: an
+// array element reads its index
+fn value_slots<'a>(operand: &'a Operand, slots: &mut Vec<&'a Operand>) {
+    match operand {
+        Operand::Index(_, index) => value_slots(index, slots),
+        _ => slots.push(operand),
+    }
+}
+
+fn value_slots_mut<'a>(operand: &'a mut Operand, slots: &mut Vec<&'a mut Operand>) {
+    match operand {
+        Operand::Index(_, index) => value_slots_mut(index, slots),
+        _ => slots.push(operand),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl Operator {
+    // the Operator of a MGol operator
+    pub fn from_lexeme(lexeme: &str) -> Option<Operator> {
+        match lexeme {
+            "+" => Some(Operator::Add),
+            "-" => Some(Operator::Sub),
+            "*" => Some(Operator::Mul),
+            "/" => Some(Operator::Div),
+            "<" => Some(Operator::Lt),
+            "<=" => Some(Operator::Le),
+            ">" => Some(Operator::Gt),
+            ">=" => Some(Operator::Ge),
+            "=" => Some(Operator::Eq),
+            "<>" => Some(Operator::Ne),
+            "e" => Some(Operator::And),
+            "ou" => Some(Operator::Or),
+            _ => None,
+        }
+    }
+
+    // the MGol lexeme of the operator
+    pub fn lexeme(&self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Eq => "=",
+            Operator::Ne => "<>",
+            Operator::And => "e",
+            Operator::Or => "ou",
+        }
+    }
+
+    // a relational or a logical operator gives a logico
+    pub fn is_relational(&self) -> bool {
+        !matches!(
+            self,
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Div
+        )
+    }
+}
+
+// The instructions. The Type in them is the type of the values they
+// work on: in a BinOp it is the type the operands are taken as (real
+// when one of them is real), not the type of the result.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Instr {
+    // target = value
+    Assign(Operand, Operand, Type),
+    // target = left operator right
+    BinOp(Operand, Operand, Operator, Operand, Type),
+    Read(Operand, Type),
+    Write(Operand, Type),
+    Label(usize),
+    Goto(usize),
+    // if_false condition goto label
+    IfFalse(Operand, usize),
+    // [target =] call name(args)
+    Call(Option<Operand>, String, Vec<Operand>),
+    Return(Option<Operand>),
+    // stop the program when index is not in [0, size); the row is the
+    // line of the access in the source code
+    CheckIndex(Operand, usize, usize),
+}
+
+impl Instr {
+    // the operand the instruction writes to
+    pub fn target(&self) -> Option<&Operand> {
+        match self {
+            Instr::Assign(target, ..) | Instr::BinOp(target, ..) | Instr::Read(target, _) => Some(target),
+            Instr::Call(target, ..) => target.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn target_mut(&mut self) -> Option<&mut Operand> {
+        match self {
+            Instr::Assign(target, ..) | Instr::BinOp(target, ..) | Instr::Read(target, _) => Some(target),
+            Instr::Call(target, ..) => target.as_mut(),
+            _ => None,
+        }
+    }
+
+    // The operands the instruction reads, one for each variable or
+    // constant: the index of an array element is read, even when the
+    // element is the target.
+    pub fn uses(&self) -> Vec<&Operand> {
+        let mut slots = Vec::new();
+        if let Some(Operand::Index(_, index)) = self.target() {
+            value_slots(index, &mut slots);
+        }
+        match self {
+            Instr::Assign(_, value, _)
+            | Instr::Write(value, _)
+            | Instr::IfFalse(value, _)
+            | Instr::Return(Some(value))
+            | Instr::CheckIndex(value, ..) => value_slots(value, &mut slots),
+            Instr::BinOp(_, left, _, right, _) => {
+                value_slots(left, &mut slots);
+                value_slots(right, &mut slots);
+            }
+            Instr::Call(_, _, args) => {
+                for arg in args {
+                    value_slots(arg, &mut slots);
+                }
+            }
+            _ => (),
+        }
+        slots
+    }
+
+    pub fn uses_mut(&mut self) -> Vec<&mut Operand> {
+        let mut slots = Vec::new();
+        match self {
+            Instr::Assign(target, value, _) => {
+                if let Operand::Index(_, index) = target {
+                    value_slots_mut(index, &mut slots);
+                }
+                value_slots_mut(value, &mut slots);
+            }
+            Instr::BinOp(target, left, _, right, _) => {
+                if let Operand::Index(_, index) = target {
+                    value_slots_mut(index, &mut slots);
+                }
+                value_slots_mut(left, &mut slots);
+                value_slots_mut(right, &mut slots);
+            }
+            Instr::Read(Operand::Index(_, index), _) => value_slots_mut(index, &mut slots),
+            Instr::Write(value, _)
+            | Instr::IfFalse(value, _)
+            | Instr::Return(Some(value))
+            | Instr::CheckIndex(value, ..) => value_slots_mut(value, &mut slots),
+            Instr::Call(target, _, args) => {
+                if let Some(Operand::Index(_, index)) = target {
+                    value_slots_mut(index, &mut slots);
+                }
+                for arg in args {
+                    value_slots_mut(arg, &mut slots);
+                }
+            }
+            _ => (),
+        }
+        slots
+    }
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instr::Assign(target, value, _) => write!(f, "    {} = {}", target, value),
+            Instr::BinOp(target, left, operator, right, _) => {
+                write!(f, "    {} = {} {} {}", target, left, operator.lexeme(), right)
+            }
+            Instr::Read(target, tk_type) => write!(f, "    read {} ({})", target, tk_type),
+            Instr::Write(value, tk_type) => write!(f, "    write {} ({})", value, tk_type),
+            Instr::Label(label) => write!(f, "L{}:", label),
+            Instr::Goto(label) => write!(f, "    goto L{}", label),
+            Instr::IfFalse(condition, label) => {
+                write!(f, "    if_false {} goto L{}", condition, label)
+            }
+            Instr::Call(target, name, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                match target {
+                    Some(target) => write!(f, "    {} = call {}({})", target, name, args.join(", ")),
+                    None => write!(f, "    call {}({})", name, args.join(", ")),
+                }
+            }
+            Instr::Return(Some(value)) => write!(f, "    return {}", value),
+            Instr::Return(None) => write!(f, "    return"),
+            Instr::CheckIndex(index, size, row) => {
+                write!(f, "    check_index {}, {} (linha {})", index, size, row)
+            }
+        }
+    }
+}
+
+fn renumber(operand: &mut Operand, numbers: &[usize]) {
+    match operand {
+        Operand::Temp(n) => *n = numbers[*n],
+        Operand::Index(_, index) => renumber(index, numbers),
+        _ => (),
+    }
+}
+
+// a variable declaration: name, type and the size of an array
+#[derive(Clone, PartialEq, Debug)]
+pub struct Var {
+    pub name: String,
+    pub tk_type: Type,
+    pub size: Option<usize>,
+}
+
+// A subprogram or the main program. The temporary variable Tn has
+// the type temps[n] and the labels are numbered from 0 to labels - 1.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Function {
+    pub name: String,
+    pub return_type: Option<Type>,
+    pub params: Vec<Var>,
+    pub locals: Vec<Var>,
+    pub temps: Vec<Type>,
+    pub labels: usize,
+    pub code: Vec<Instr>,
+}
+
+impl Function {
+    pub fn new(name: &str, return_type: Option<Type>, params: Vec<Var>) -> Function {
+        Function {
+            name: String::from(name),
+            return_type,
+            params,
+            locals: Vec::new(),
+            temps: Vec::new(),
+            labels: 0,
+            code: Vec::new(),
+        }
+    }
+
+    // the declaration of a parameter or local variable of the function
+    pub fn get_var(&self, name: &str) -> Option<&Var> {
+        self.params.iter().chain(self.locals.iter()).find(|var| var.name == name)
+    }
+
+    // Give the temporary variable Tn the number numbers[n]; temps are
+    // the types of the new ones. Two temporaries may get the same number.
+    pub fn renumber_temps(&mut self, numbers: &[usize], temps: Vec<Type>) {
+        // the index of a target is renumbered with the other uses
+        for instr in &mut self.code {
+            if let Some(target @ Operand::Temp(_)) = instr.target_mut() {
+                renumber(target, numbers);
+            }
+            for operand in instr.uses_mut() {
+                renumber(operand, numbers);
+            }
+        }
+        self.temps = temps;
+    }
+
+    // the type of an operand used in the function
+    pub fn type_of(&self, globals: &[Var], operand: &Operand) -> Type {
+        match operand {
+            Operand::Int(_) => Type::Inteiro,
+            Operand::Real(_) => Type::Real,
+            Operand::Lit(_) => Type::Literal,
+            Operand::Temp(n) => self.temps[*n],
+            Operand::Var(name) | Operand::Index(name, _) => self
+                .get_var(name)
+                .or_else(|| globals.iter().find(|var| &var.name == name))
+                .map(|var| var.tk_type)
+                .unwrap_or(Type::Inteiro),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Program {
+    pub globals: Vec<Var>,
+    pub subprograms: Vec<Function>,
+    pub main: Function,
+}
+
+impl Program {
+    // the function with the given name, the main program included
+    pub fn get_function(&self, name: &str) -> Option<&Function> {
+        self.subprograms.iter().chain(Some(&self.main)).find(|function| function.name == name)
+    }
+
+    // the declaration of a variable seen from a function: its own
+    // ones hide the global ones
+    pub fn get_var<'a>(&'a self, function: &'a Function, name: &str) -> Option<&'a Var> {
+        function
+            .get_var(name)
+            .or_else(|| self.globals.iter().find(|var| var.name == name))
+    }
+
+    // the type of an operand used in a function
+    pub fn type_of(&self, function: &Function, operand: &Operand) -> Type {
+        function.type_of(&self.globals, operand)
+    }
+}
+
+fn write_var(f: &mut fmt::Formatter, kind: &str, var: &Var) -> fmt::Result {
+    match var.size {
+        Some(size) => writeln!(f, "    {} {} {}[{}]", kind, var.tk_type, var.name, size),
+        None => writeln!(f, "    {} {} {}", kind, var.tk_type, var.name),
+    }
+}
+
+// the declarations and the code of a function, up to its end
+fn write_body(f: &mut fmt::Formatter, function: &Function) -> fmt::Result {
+    for var in &function.locals {
+        write_var(f, "local", var)?;
+    }
+    for (n, tk_type) in function.temps.iter().enumerate() {
+        writeln!(f, "    temp {} T{}", tk_type, n)?;
+    }
+    for instr in &function.code {
+        writeln!(f, "{}", instr)?;
+    }
+    writeln!(f, "fim")
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| format!("{} {}", param.tk_type, param.name))
+            .collect();
+        match self.return_type {
+            Some(return_type) => writeln!(f, "funcao {} {}({})", return_type, self.name, params.join(", "))?,
+            None => writeln!(f, "procedimento {}({})", self.name, params.join(", "))?,
+        }
+        write_body(f, self)
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.globals.is_empty() {
+            writeln!(f, "global")?;
+            for var in &self.globals {
+                write_var(f, "var", var)?;
+            }
+            writeln!(f, "fim")?;
+            writeln!(f)?;
+        }
+        for function in &self.subprograms {
+            writeln!(f, "{}", function)?;
+        }
+        writeln!(f, "programa")?;
+        write_body(f, &self.main)
+    }
+}
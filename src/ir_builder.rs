@@ -0,0 +1,262 @@
+use super::backend::Backend;
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// a statement whose end is still to come, with its labels
+enum Block {
+    If { else_label: usize, end_label: Option<usize> },
+    Loop { begin: usize, end: usize },
+    For { var: Operand, step: Operand, begin: usize, end: usize },
+}
+
+// The Backend that builds the three-address code of the program. The
+// structured statements become labels, goto and if_false.
+pub struct IrBuilder {
+    program: Program,
+    in_subprogram: bool,
+    blocks: Vec<Block>,
+}
+
+impl Default for IrBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IrBuilder {
+    pub fn new() -> IrBuilder {
+        IrBuilder {
+            program: Program {
+                globals: Vec::new(),
+                subprograms: Vec::new(),
+                main: Function::new("main", None, Vec::new()),
+            },
+            in_subprogram: false,
+            blocks: Vec::new(),
+        }
+    }
+
+    // the program built so far
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn into_program(self) -> Program {
+        self.program
+    }
+
+    fn current_function(&mut self) -> &mut Function {
+        if self.in_subprogram {
+            self.program.subprograms.last_mut().unwrap()
+        } else {
+            &mut self.program.main
+        }
+    }
+
+    fn emit(&mut self, instr: Instr) {
+        self.current_function().code.push(instr);
+    }
+
+    fn new_label(&mut self) -> usize {
+        let function = self.current_function();
+        function.labels += 1;
+        function.labels - 1
+    }
+
+    fn type_of(&self, operand: &Operand) -> Type {
+        let function = if self.in_subprogram {
+            self.program.subprograms.last().unwrap()
+        } else {
+            &self.program.main
+        };
+        self.program.type_of(function, operand)
+    }
+}
+
+impl Backend for IrBuilder {
+    // the program variables are global, the subprogram ones are local
+    fn declare_variable(&mut self, name: &str, tk_type: Type, size: Option<usize>) {
+        let var = Var {
+            name: String::from(name),
+            tk_type,
+            size,
+        };
+        if self.in_subprogram {
+            self.current_function().locals.push(var);
+        } else {
+            self.program.globals.push(var);
+        }
+    }
+
+    fn end_declarations(&mut self) {}
+
+    fn new_temp(&mut self, tk_type: Type) -> Operand {
+        let temps = &mut self.current_function().temps;
+        temps.push(tk_type);
+
+        Operand::Temp(temps.len() - 1)
+    }
+
+    fn index(&mut self, array: &str, index: Operand) -> Operand {
+        Operand::Index(String::from(array), Box::new(index))
+    }
+
+    // the index is checked where the element is accessed
+    fn checked_index(&mut self, array: &str, index: Operand, size: usize, row: usize) -> Operand {
+        self.emit(Instr::CheckIndex(index.clone(), size, row));
+        self.index(array, index)
+    }
+
+    fn read(&mut self, target: Operand, tk_type: Type) {
+        self.emit(Instr::Read(target, tk_type));
+    }
+
+    fn write(&mut self, args: Vec<(Operand, Type)>) {
+        for (value, tk_type) in args {
+            self.emit(Instr::Write(value, tk_type));
+        }
+    }
+
+    fn assign(&mut self, target: Operand, value: Operand, tk_type: Type) {
+        self.emit(Instr::Assign(target, value, tk_type));
+    }
+
+    // an inteiro is compared with a real as a real
+    fn binary_op(&mut self, target: Operand, left: Operand, operator: Operator, right: Operand) {
+        let tk_type = match (self.type_of(&left), self.type_of(&right)) {
+            (Type::Real, _) | (_, Type::Real) => Type::Real,
+            (tk_type, _) => tk_type,
+        };
+        self.emit(Instr::BinOp(target, left, operator, right, tk_type));
+    }
+
+    fn call(&mut self, target: Option<Operand>, name: &str, args: Vec<Operand>) {
+        self.emit(Instr::Call(target, String::from(name), args));
+    }
+
+    // if_false condition goto else
+    fn if_begin(&mut self, condition: Operand) {
+        let else_label = self.new_label();
+        self.emit(Instr::IfFalse(condition, else_label));
+        self.blocks.push(Block::If { else_label, end_label: None });
+    }
+
+    // the end of the then part jumps over the else one
+    fn else_begin(&mut self) {
+        if let Some(Block::If { else_label, end_label: None }) = self.blocks.pop() {
+            let end_label = self.new_label();
+            self.emit(Instr::Goto(end_label));
+            self.emit(Instr::Label(else_label));
+            self.blocks.push(Block::If { else_label, end_label: Some(end_label) });
+        }
+    }
+
+    fn if_end(&mut self) {
+        if let Some(Block::If { else_label, end_label }) = self.blocks.pop() {
+            self.emit(Instr::Label(end_label.unwrap_or(else_label)));
+        }
+    }
+
+    fn loop_begin(&mut self) {
+        let begin = self.new_label();
+        let end = self.new_label();
+        self.emit(Instr::Label(begin));
+        self.blocks.push(Block::Loop { begin, end });
+    }
+
+    fn loop_exit_unless(&mut self, condition: Operand) {
+        if let Some(Block::Loop { end, .. }) = self.blocks.last() {
+            let end = *end;
+            self.emit(Instr::IfFalse(condition, end));
+        }
+    }
+
+    fn loop_end(&mut self) {
+        if let Some(Block::Loop { begin, end }) = self.blocks.pop() {
+            self.emit(Instr::Goto(begin));
+            self.emit(Instr::Label(end));
+        }
+    }
+
+    // The condition is tested before each repetition. The direction of
+    // a num step is known now, but an id one may change from one run
+    // to another, so both comparisons are generated.
+    fn for_begin(&mut self, var: Operand, start: Operand, end: Operand, step: Operand) {
+        self.emit(Instr::Assign(var.clone(), start, Type::Inteiro));
+
+        let begin_label = self.new_label();
+        let end_label = self.new_label();
+        self.emit(Instr::Label(begin_label));
+        let condition = self.new_temp(Type::Logico);
+        match step {
+            Operand::Int(n) => {
+                let operator = if n < 0 { Operator::Ge } else { Operator::Le };
+                self.emit(Instr::BinOp(condition.clone(), var.clone(), operator, end, Type::Inteiro));
+            }
+            _ => {
+                let down_label = self.new_label();
+                let test_label = self.new_label();
+                let up = self.new_temp(Type::Logico);
+                self.emit(Instr::BinOp(up.clone(), step.clone(), Operator::Ge, Operand::Int(0), Type::Inteiro));
+                self.emit(Instr::IfFalse(up, down_label));
+                self.emit(Instr::BinOp(condition.clone(), var.clone(), Operator::Le, end.clone(), Type::Inteiro));
+                self.emit(Instr::Goto(test_label));
+                self.emit(Instr::Label(down_label));
+                self.emit(Instr::BinOp(condition.clone(), var.clone(), Operator::Ge, end, Type::Inteiro));
+                self.emit(Instr::Label(test_label));
+            }
+        }
+        self.emit(Instr::IfFalse(condition, end_label));
+
+        self.blocks.push(Block::For {
+            var,
+            step,
+            begin: begin_label,
+            end: end_label,
+        });
+    }
+
+    fn for_end(&mut self) {
+        if let Some(Block::For { var, step, begin, end }) = self.blocks.pop() {
+            self.emit(Instr::BinOp(var.clone(), var, Operator::Add, step, Type::Inteiro));
+            self.emit(Instr::Goto(begin));
+            self.emit(Instr::Label(end));
+        }
+    }
+
+    // from now on, the code goes into the subprogram
+    fn subprogram_begin(&mut self, name: &str, return_type: Option<Type>, params: &[(String, Type)]) {
+        let params = params
+            .iter()
+            .map(|(name, tk_type)| Var {
+                name: name.clone(),
+                tk_type: *tk_type,
+                size: None,
+            })
+            .collect();
+        self.program
+            .subprograms
+            .push(Function::new(name, return_type, params));
+        self.in_subprogram = true;
+    }
+
+    fn return_value(&mut self, value: Option<Operand>) {
+        self.emit(Instr::Return(value));
+    }
+
+    // A subprogram may reach its end without a 'retorne': a function
+    // returns 0 (or an empty literal) there. From now on, the code goes
+    // into the main program.
+    fn subprogram_end(&mut self) {
+        let function = self.current_function();
+        if !matches!(function.code.last(), Some(Instr::Return(_))) {
+            let value = match function.return_type {
+                None => None,
+                Some(Type::Real) => Some(Operand::Real(0.0)),
+                Some(Type::Literal) => Some(Operand::Lit(String::new())),
+                Some(_) => Some(Operand::Int(0)),
+            };
+            self.emit(Instr::Return(value));
+        }
+        self.in_subprogram = false;
+    }
+}
@@ -1,14 +1,16 @@
 // All allowed automaton states
 pub enum AutomatonState {
     Initial,         // 0
-    Accept(u8),      // 1, 2, ..., 18
-    NonAccept(char), // a, b, c, d, e
+    Accept(u8),      // 1, 2, ..., 22
+    NonAccept(char), // a, b, c, d, e, f, g, h, i
     Error(u8),       // lexical error - 0: character isn't in the alphabet
                      //                 1: character doesn't start a token
                      //                 2: no digit after a '.' in a num token
                      //                 3: no digit, '+' or '-' after a 'e'/'E' in a num token
                      //                 4: no digit after a ('e'/'E')('+''-')  in a num token
                      //                 5: unfinished comment or literal
+                     //                 6: accented letter in an id
+                     //                 7: literal not closed before the end of its line
 }
 
 // Actions to be performed by the Scanner using the the automaton
@@ -23,12 +25,20 @@ pub enum Action {
 // A struct to represent an automaton used in the lexical
 // analysis. Internally, it keeps an AutomatonState (current
 // automaton state), a bool variable done (when done == true
-// the automaton reached an accept state) and an Action (to be
-// performed by the Scanner).
+// the automaton reached an accept state), an Action (to be
+// performed by the Scanner) and how many '{' comments are open
+// inside the current one.
 pub struct Automaton {
     pub state: AutomatonState,
     pub done: bool,
     pub action: Action,
+    comment_depth: usize,
+}
+
+impl Default for Automaton {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Automaton {
@@ -38,6 +48,7 @@ impl Automaton {
             state: AutomatonState::Initial,
             done: false,
             action: Action::None,
+            comment_depth: 0,
         }
     }
 
@@ -53,11 +64,15 @@ impl Automaton {
                 '{' => self.state = AutomatonState::NonAccept('e'),
                 '<' => self.state = AutomatonState::Accept(8),
                 '>' => self.state = AutomatonState::Accept(12),
-                '=' => self.state = AutomatonState::Accept(14),
-                '+' | '-' | '*' | '/' => {
+                '=' => {
+                    self.done = true;
+                    self.state = AutomatonState::Accept(14);
+                }
+                '+' | '-' | '*' => {
                     self.done = true;
                     self.state = AutomatonState::Accept(15);
                 }
+                '/' => self.state = AutomatonState::Accept(22),
                 '(' => {
                     self.done = true;
                     self.state = AutomatonState::Accept(16);
@@ -70,11 +85,24 @@ impl Automaton {
                     self.done = true;
                     self.state = AutomatonState::Accept(18);
                 }
-                '\n' | '\r' | ' ' => {
+                ',' => {
+                    self.done = true;
+                    self.state = AutomatonState::Accept(19);
+                }
+                '[' => {
+                    self.done = true;
+                    self.state = AutomatonState::Accept(20);
+                }
+                ']' => {
+                    self.done = true;
+                    self.state = AutomatonState::Accept(21);
+                }
+                '\n' | '\r' | '\t' | ' ' => {
                     self.state = AutomatonState::Initial;
                     self.action = Action::None;
                 }
                 c if is_in_alphabet(c) => self.error(1),
+                c if c.is_alphabetic() => self.error(6),
                 _ => self.error(0),
             },
             AutomatonState::Accept(1) => match c {
@@ -112,6 +140,7 @@ impl Automaton {
                     self.done = true;
                     self.action = Action::GoBack;
                 }
+                c if c.is_alphabetic() => self.error(6),
                 _ => self.error(0),
             },
             AutomatonState::Accept(8) => match c {
@@ -162,18 +191,69 @@ impl Automaton {
                     self.done = true;
                     self.state = AutomatonState::Accept(4);
                 }
-                c if is_in_alphabet(c) => {
-                    self.state = AutomatonState::NonAccept('d');
+                '\\' => self.state = AutomatonState::NonAccept('f'),
+                // a literal must end in the line it starts
+                '\n' => self.error(7),
+                // any character, not only the ones in the alphabet,
+                // may be written inside a literal
+                _ => self.state = AutomatonState::NonAccept('d'),
+            },
+            // escape sequence in a literal
+            AutomatonState::NonAccept('f') => match c {
+                c if is_escape(c) => self.state = AutomatonState::NonAccept('d'),
+                '\n' => self.error(7),
+                _ => {
+                    self.state = AutomatonState::NonAccept('g');
+                    self.action = Action::ShowError;
+                }
+            },
+            // Rest of a literal with an unknown escape sequence. The error
+            // was already shown, so the literal is still accepted and the
+            // analysis goes on as usual.
+            AutomatonState::NonAccept('g') => match c {
+                '"' => {
+                    self.done = true;
+                    self.state = AutomatonState::Accept(4);
+                }
+                '\\' => self.state = AutomatonState::NonAccept('h'),
+                '\n' => self.error(7),
+                _ => self.state = AutomatonState::NonAccept('g'),
+            },
+            // escape sequence in a literal with an unknown one
+            AutomatonState::NonAccept('h') => match c {
+                '\n' => self.error(7),
+                c if is_escape(c) => self.state = AutomatonState::NonAccept('g'),
+                _ => {
+                    self.state = AutomatonState::NonAccept('g');
+                    self.action = Action::ShowError;
                 }
-                _ => self.error(0),
             },
             AutomatonState::NonAccept('e') => match c {
+                // comments may be nested
+                '{' => self.comment_depth += 1,
+                '}' if self.comment_depth > 0 => self.comment_depth -= 1,
                 '}' => {
                     self.state = AutomatonState::Initial;
                     self.action = Action::ClearLexeme;
                 }
-                c if is_in_alphabet(c) => self.state = AutomatonState::NonAccept('e'),
-                _ => self.error(0),
+                // the same goes for comments
+                _ => self.state = AutomatonState::NonAccept('e'),
+            },
+            // a '/' is a division, unless another one follows it
+            AutomatonState::Accept(22) => match c {
+                '/' => self.state = AutomatonState::NonAccept('i'),
+                _ => {
+                    self.done = true;
+                    self.action = Action::GoBack;
+                }
+            },
+            // a '//' comment goes up to the end of the line
+            AutomatonState::NonAccept('i') => match c {
+                '\n' => {
+                    self.state = AutomatonState::Initial;
+                    self.action = Action::ClearLexeme;
+                }
+                _ => self.state = AutomatonState::NonAccept('i'),
             },
             _ => (),
         }
@@ -190,19 +270,24 @@ impl Automaton {
 
 // check if a characters is in the MGol alphabet
 fn is_in_alphabet(c: char) -> bool {
-    match c {
-        '0'..='9' => true,
-        'a'..='z' => true,
-        'A'..='Z' => true,
-        ',' | '.' | ';' | ':' => true,
-        '<' | '>' | '=' => true,
-        '(' | ')' => true,
-        '[' | ']' => true,
-        '{' | '}' => true,
-        '+' | '-' | '*' | '/' => true,
-        '!' | '?' | '\\' => true,
-        '"' | '\'' => true,
-        '\n' | '\r' | ' ' => true,
-        _ => false,
-    }
+    matches!(
+        c,
+        '0'..='9'
+            | 'a'..='z'
+            | 'A'..='Z'
+            | ',' | '.' | ';' | ':'
+            | '<' | '>' | '='
+            | '(' | ')'
+            | '[' | ']'
+            | '{' | '}'
+            | '+' | '-' | '*' | '/'
+            | '!' | '?' | '\\'
+            | '"' | '\''
+            | '\n' | '\r' | '\t' | ' '
+    )
+}
+
+// check if a character may follow a '\\' in a literal
+fn is_escape(c: char) -> bool {
+    matches!(c, 'n' | 't' | '"' | '\\')
 }
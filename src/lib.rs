@@ -1,8 +1,19 @@
 pub mod token;
 pub mod scanner;
+pub mod encoding;
 pub mod lexical_automaton;
 pub mod symbol_table;
 pub mod grammar;
 pub mod slr_table;
 pub mod parser;
-pub mod obj_file;
\ No newline at end of file
+pub mod backend;
+pub mod ir;
+pub mod ir_builder;
+pub mod optimizer;
+pub mod liveness;
+pub mod interpreter;
+pub mod bytecode;
+pub mod vm;
+pub mod obj_file;
+pub mod wat_file;
+pub mod asm_file;
\ No newline at end of file
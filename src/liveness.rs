@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use super::ir::{Function, Instr, Operand, Program};
+
+// the instructions that may run right after instruction i
+fn successors(function: &Function, i: usize) -> Vec<usize> {
+    let label_at = |label: usize| {
+        function
+            .code
+            .iter()
+            .position(|instr| *instr == Instr::Label(label))
+    };
+    match function.code[i] {
+        Instr::Goto(label) => label_at(label).into_iter().collect(),
+        Instr::IfFalse(_, label) => label_at(label).into_iter().chain(Some(i + 1)).collect(),
+        Instr::Return(_) => Vec::new(),
+        _ => vec![i + 1],
+    }
+    .into_iter()
+    .filter(|next| *next < function.code.len())
+    .collect()
+}
+
+// The temporary variables live after each instruction: the ones that
+// may be read later, before being written again.
+pub fn live_temps(function: &Function) -> Vec<HashSet<usize>> {
+    let uses: Vec<HashSet<usize>> = function
+        .code
+        .iter()
+        .map(|instr| instr.uses().into_iter().filter_map(Operand::temp).collect())
+        .collect();
+    let defs: Vec<Option<usize>> = function
+        .code
+        .iter()
+        .map(|instr| instr.target().and_then(Operand::temp))
+        .collect();
+    let successors: Vec<Vec<usize>> = (0..function.code.len())
+        .map(|i| successors(function, i))
+        .collect();
+
+    // live_in = uses + (live_out - defs), from the last instruction up,
+    // until nothing changes
+    let mut live_in = vec![HashSet::new(); function.code.len()];
+    let mut live_out = vec![HashSet::new(); function.code.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in (0..function.code.len()).rev() {
+            let out: HashSet<usize> = successors[i]
+                .iter()
+                .flat_map(|next| live_in[*next].iter().copied())
+                .collect();
+            let mut inn: HashSet<usize> = out.iter().copied().filter(|n| Some(*n) != defs[i]).collect();
+            inn.extend(uses[i].iter().copied());
+
+            if inn != live_in[i] || out != live_out[i] {
+                live_in[i] = inn;
+                live_out[i] = out;
+                changed = true;
+            }
+        }
+    }
+    live_out
+}
+
+// Two temporary variables of the same type share a number when one is
+// never written while the other is live, so each function declares as
+// few of them as it can.
+pub fn reuse_temps(program: &mut Program) {
+    let Program { subprograms, main, .. } = program;
+
+    for function in subprograms.iter_mut().chain(Some(main)) {
+        let live_out = live_temps(function);
+        let mut interferes = vec![HashSet::new(); function.temps.len()];
+        for (i, instr) in function.code.iter().enumerate() {
+            if let Some(n) = instr.target().and_then(Operand::temp) {
+                for m in live_out[i].iter().copied().filter(|m| *m != n) {
+                    interferes[n].insert(m);
+                    interferes[m].insert(n);
+                }
+            }
+        }
+
+        // each one gets the first number of its type none of the
+        // temporaries it interferes with has
+        let mut numbers: Vec<usize> = Vec::new();
+        let mut temps = Vec::new();
+        for (n, tk_type) in function.temps.iter().enumerate() {
+            let taken: HashSet<usize> = interferes[n]
+                .iter()
+                .filter(|m| **m < n)
+                .map(|m| numbers[*m])
+                .collect();
+            let number = (0..temps.len())
+                .find(|number| temps[*number] == *tk_type && !taken.contains(number))
+                .unwrap_or(temps.len());
+            if number == temps.len() {
+                temps.push(*tk_type);
+            }
+            numbers.push(number);
+        }
+        function.renumber_temps(&numbers, temps);
+    }
+}
@@ -1,19 +1,139 @@
-use std::fs::File;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::process;
+use mgol::asm_file::AsmFile;
+use mgol::bytecode::Module;
+use mgol::encoding::Encoding;
+use mgol::interpreter;
+use mgol::ir_builder::IrBuilder;
+use mgol::liveness;
+use mgol::obj_file::ObjFile;
+use mgol::optimizer;
 use mgol::scanner::Scanner;
 use mgol::parser::Parser;
+use mgol::vm;
+use mgol::wat_file::WatFile;
 
 fn main() {
+    // the source file is the first argument that is not an option and
+    // the output file is given by -o; with 'mgol run' the program runs
+    // instead of being compiled and 'mgol dis' shows a .mgb file
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let run = args.first().is_some_and(|arg| arg == "run");
+    let dis = args.first().is_some_and(|arg| arg == "dis");
+    if run || dis {
+        args.remove(0);
+    }
+    let mut path = String::from("./test/teste.mgol");
+    let mut output = None;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "-o" && i + 1 < args.len() {
+            output = Some(args[i + 1].clone());
+            i += 1;
+        } else if !args[i].starts_with('-') {
+            path = args[i].clone();
+        }
+        i += 1;
+    }
+
+    // a .mgb file is run (or shown) without being analysed again
+    if run && path.ends_with(".mgb") || dis {
+        let module = match fs::read(&path) {
+            Err(_) => panic!("Não foi possível abrir o arquivo {}", path),
+            Ok(bytes) => Module::from_bytes(&bytes),
+        };
+        let module = match module {
+            Err(msg) => {
+                eprintln!("Erro no arquivo {}: {}", path, msg);
+                process::exit(1);
+            }
+            Ok(module) => module,
+        };
+        if dis {
+            print!("{}", module);
+            return;
+        }
+        process::exit(vm::run(&module, io::stdin().lock(), io::BufWriter::new(io::stdout().lock())));
+    }
+
     // open the file
-    let path = "./test/teste.mgol";
-    let file = match File::open(path) {
+    let file = match File::open(&path) {
         Err(_) => panic!("Não foi possível abrir o arquivo {}", path),
         Ok(file) => file,
     };
 
     // start the scanner
     let mut scanner = Scanner::new(file);
+    // the source file may not be in UTF-8
+    if let Some(name) = env::args().find_map(|arg| arg.strip_prefix("--encoding=").map(String::from)) {
+        match Encoding::from_name(&name) {
+            Some(encoding) => scanner.set_encoding(encoding),
+            None => panic!("Codificação desconhecida: {} (use utf-8, latin1 ou cp1252)", name),
+        }
+    }
+    // the columns in the error messages follow the student's editor
+    if let Some(tab_width) = env::args()
+        .find_map(|arg| arg.strip_prefix("--tab-width=").and_then(|n| n.parse().ok()))
+    {
+        scanner.set_tab_width(tab_width);
+    }
 
     // start the parser and give it the scanner to begin the syntactic analysis
     let mut parser = Parser::new();
-    parser.parse(&mut scanner);
+    // the array indexes may also be checked at runtime
+    parser.set_bounds_check(env::args().any(|arg| arg == "--bounds-check"));
+    // what a running program writes is not mixed with the reductions
+    parser.set_show_reductions(!run);
+
+    // the output is the obj file in C, the bytecode with --emit=mgb, a
+    // WebAssembly module with --emit=wat, x86-64 assembly with --emit=asm
+    // or, with --emit=ir, the intermediate code (written to the standard
+    // output when there is no -o)
+    let emit = env::args()
+        .find_map(|arg| arg.strip_prefix("--emit=").map(String::from))
+        .unwrap_or(String::from("c"));
+    if !["c", "ir", "mgb", "wat", "asm"].contains(&emit.as_str()) {
+        panic!("Saída desconhecida: {} (use c, ir, mgb, wat ou asm)", emit);
+    }
+
+    // the output is written only when the program has no errors
+    let mut ir_builder = IrBuilder::new();
+    if !parser.parse(&mut scanner, &mut ir_builder) {
+        if run {
+            process::exit(1);
+        }
+        return;
+    }
+    let mut program = ir_builder.into_program();
+    // with -O the intermediate code is optimized, telling what was done
+    if env::args().any(|arg| arg == "-O") {
+        let report = optimizer::optimize(&mut program);
+        if !run {
+            print!("{}", report);
+        }
+    }
+    if run {
+        process::exit(interpreter::run(&program, io::stdin().lock(), io::BufWriter::new(io::stdout().lock())));
+    }
+    // a temporary variable is reused once its value is no longer needed
+    liveness::reuse_temps(&mut program);
+    match (emit.as_str(), output) {
+        ("ir", None) => print!("{}", program),
+        ("ir", Some(output)) => {
+            if fs::write(&output, program.to_string()).is_err() {
+                panic!("Não foi possível escrever o código intermediário {}", output);
+            }
+        }
+        ("mgb", output) => {
+            let output = output.unwrap_or(String::from("./PROGRAMA.mgb"));
+            if fs::write(&output, Module::compile(&program).to_bytes()).is_err() {
+                panic!("Não foi possível escrever o bytecode {}", output);
+            }
+        }
+        ("wat", output) => WatFile::new(&program).create(&output.unwrap_or(String::from("./PROGRAMA.wat"))),
+        ("asm", output) => AsmFile::new(&program).create(&output.unwrap_or(String::from("./PROGRAMA.s"))),
+        (_, output) => ObjFile::new(&program).create(&output.unwrap_or(String::from("./PROGRAMA.c"))),
+    }
 }
@@ -1,67 +1,576 @@
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
+use std::ops::Range;
+use super::ir::{Function, Instr, Operand, Operator, Program, Type};
+use super::liveness::live_temps;
 
-pub enum TempVarType {
-    Int,
-    Real,
+// the C operator for a MGol one: MGol compares with = and <> and has
+// words for the logical operators
+fn c_operator(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Eq => "==",
+        Operator::Ne => "!=",
+        Operator::And => "&&",
+        Operator::Or => "||",
+        _ => operator.lexeme(),
+    }
 }
 
-pub struct ObjFile {
-    temp_vars: Vec<TempVarType>,
+// The C name of a variable of the program: a temporary variable is
+// Tn, so a MGol name gets a prefix to keep the two apart, and to keep
+// it apart from the C keywords and the names of the C library too.
+fn var_name(name: &str) -> String {
+    format!("v_{}", name)
+}
+
+// the C name of a subprogram, for the same reasons
+fn function_name(name: &str) -> String {
+    format!("f_{}", name)
+}
+
+// A literal as the text of a C string. The MGol escape sequences are
+// the same in C, but a '?' is escaped so two of them are not taken as
+// a trigraph.
+fn c_string(text: &str) -> String {
+    text.replace('?', "\\?")
+}
+
+// an operand as a C expression
+fn c_operand(operand: &Operand) -> String {
+    match operand {
+        Operand::Var(name) => var_name(name),
+        Operand::Index(array, index) => format!("{}[{}]", var_name(array), c_operand(index)),
+        Operand::Lit(text) => format!("\"{}\"", c_string(text)),
+        _ => operand.to_string(),
+    }
+}
+
+// C functions the generated code may call, written in the obj file
+// only when they are used
+#[derive(PartialEq)]
+enum Helper {
+    CheckedIndex,
+    ReadLogico,
+    CopyLiteral,
+}
+
+// A piece of the obj file with its own temporary variables: the
+// main function or one of the subprograms. The header is the C
+// function signature. The temporary variables are the (n, type) of
+// each Tn the C code uses.
+struct Unit {
+    header: String,
+    temp_vars: Vec<(usize, Type)>,
     content: Vec<String>,
 }
 
-impl ObjFile {
-    pub fn new() -> ObjFile {
-        ObjFile {
-            temp_vars: Vec::new(),
-            content: Vec::new(),
+impl Unit {
+    fn write(&self, file: &mut File) -> io::Result<()> {
+        writeln!(file, "{}", self.header)?;
+        writeln!(file, "{{")?;
+        if !self.temp_vars.is_empty() {
+            writeln!(file, "    /*----Variaveis temporarias----*/")?;
+            for (n, temp_var_type) in &self.temp_vars {
+                writeln!(file, "    {} T{};", temp_var_type, n)?;
+            }
+            writeln!(file, "    /*------------------------------*/")?;
         }
+
+        for line in &self.content {
+            write!(file, "{}", line)?;
+        }
+        writeln!(file, "}}")?;
+
+        Ok(())
     }
+}
+
+// The C statements of a function, with the lines that hold a label.
+// A label no goto uses is a warning in C, so it is only written when
+// one of the gotos that were not turned into a loop or a branch uses
+// it. A comparison folded into the condition of an if or of a while is
+// kept out with the if_false after it, by the position of the first.
+#[derive(Default)]
+struct Body {
+    lines: Vec<String>,
+    labels: Vec<(usize, usize)>,
+    used_labels: HashSet<usize>,
+    folded: HashSet<usize>,
+}
 
-    pub fn print(&mut self, s: String) {
-        self.content.push(s);
+impl Body {
+    fn line(&mut self, depth: usize, line: &str) {
+        self.lines.push(format!("{}{}\n", "    ".repeat(depth), line));
     }
 
-    pub fn add_temp_var(&mut self, temp_var_type: TempVarType) {
-        self.temp_vars.push(temp_var_type);
+    // a label must be followed by a statement in C99
+    fn label(&mut self, label: usize) {
+        self.labels.push((self.lines.len(), label));
+        self.lines.push(format!("L{}: ;\n", label));
     }
 
-    pub fn create(&self) {
-        let mut file = match File::create("./PROGRAMA.c") {
-            Err(_) => panic!("Não foi possível criar o código objeto PROGRAMA.c"),
-            Ok(file) => file,
+    fn goto(&mut self, label: usize) -> String {
+        self.used_labels.insert(label);
+        format!("goto L{};", label)
+    }
+
+    fn lines(mut self) -> Vec<String> {
+        for (line, label) in self.labels.iter().rev() {
+            if !self.used_labels.contains(label) {
+                self.lines.remove(*line);
+            }
+        }
+        self.lines
+    }
+}
+
+// the labels of the loop a statement is in: 'goto begin' is a
+// continue and 'goto end' a break
+#[derive(Clone, Copy)]
+struct LoopLabels {
+    begin: usize,
+    end: Option<usize>,
+}
+
+// The obj file: the C code of a program, generated from its
+// intermediate code. The MGol types are typedefs of C ones. The
+// branches and the loops of the intermediate code become if, if-else
+// and while (or for (;;)) statements in C, and a goto is only left
+// where the code has no such shape.
+pub struct ObjFile {
+    declarations: Vec<String>,
+    subprograms: Vec<Unit>,
+    main: Unit,
+    helpers: Vec<Helper>,
+}
+
+impl ObjFile {
+    pub fn new(program: &Program) -> ObjFile {
+        let mut obj_file = ObjFile {
+            declarations: Vec::new(),
+            subprograms: Vec::new(),
+            main: Unit {
+                header: String::new(),
+                temp_vars: Vec::new(),
+                content: Vec::new(),
+            },
+            helpers: Vec::new(),
         };
 
-        writeln!(file, "#include <stdio.h>");
-        writeln!(file);
-        writeln!(file, "typedef char literal[256];");
-        writeln!(file, "typedef int inteiro;");
-        writeln!(file, "typedef double real;");
-        writeln!(file);
-        writeln!(file, "void main(void)");
-        writeln!(file, "{{");
-        if self.temp_vars.len() > 0 {
-            writeln!(file, "    /*----Variaveis temporarias----*/");
-            for i in 0..self.temp_vars.len() {
-                let temp_var_type = &self.temp_vars[i];
-                match temp_var_type {
-                    TempVarType::Int => {
-                        writeln!(file, "    inteiro T{}", i);
+        // the program variables are global
+        for var in &program.globals {
+            obj_file.declarations.push(match var.size {
+                Some(size) => format!("{} {}[{}];\n", var.tk_type, var_name(&var.name), size),
+                None => format!("{} {};\n", var.tk_type, var_name(&var.name)),
+            });
+        }
+        obj_file.declarations.push(String::from("\n"));
+
+        for function in &program.subprograms {
+            let unit = obj_file.unit(function);
+            obj_file.subprograms.push(unit);
+        }
+        obj_file.main = obj_file.unit(&program.main);
+        obj_file.main.header = String::from("int main(void)");
+        obj_file.main.content.push(String::from("    return 0;\n"));
+
+        obj_file
+    }
+
+    fn use_helper(&mut self, helper: Helper) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+        }
+    }
+
+    fn unit(&mut self, function: &Function) -> Unit {
+        let mut content = Vec::new();
+
+        // A literal argument is the address of the caller's array, so it
+        // is copied into one of the subprogram: a change to the
+        // parameter must not change the caller's variable.
+        let literal_params: Vec<&str> = function
+            .params
+            .iter()
+            .filter(|param| param.tk_type == Type::Literal)
+            .map(|param| param.name.as_str())
+            .collect();
+        if !literal_params.is_empty() {
+            self.use_helper(Helper::CopyLiteral);
+            for name in &literal_params {
+                content.push(format!("    literal {};\n", var_name(name)));
+                content.push(format!("    mgol_copia_literal({}, a_{});\n", var_name(name), name));
+            }
+            content.push(String::from("\n"));
+        }
+
+        if !function.locals.is_empty() {
+            // A local variable the subprogram never reads would be a
+            // warning in C, so each one is used once in a statement that
+            // does nothing.
+            for var in &function.locals {
+                content.push(match var.size {
+                    Some(size) => format!("    {} {}[{}];\n", var.tk_type, var_name(&var.name), size),
+                    None => format!("    {} {};\n", var.tk_type, var_name(&var.name)),
+                });
+            }
+            for var in &function.locals {
+                content.push(format!("    (void) {};\n", var_name(&var.name)));
+            }
+            content.push(String::from("\n"));
+        }
+
+        let live_out = live_temps(function);
+        let mut body = Body::default();
+        self.block(function, &live_out, 0..function.code.len(), 1, None, &mut body);
+
+        // a temporary variable only a folded comparison used is not declared
+        let mut used_temps = HashSet::new();
+        for (i, instr) in function.code.iter().enumerate() {
+            let folded = body.folded.contains(&i) || (i > 0 && body.folded.contains(&(i - 1)));
+            if !folded {
+                used_temps.extend(instr.uses().into_iter().chain(instr.target()).filter_map(Operand::temp));
+            }
+        }
+        let temp_vars = function
+            .temps
+            .iter()
+            .enumerate()
+            .filter(|(n, _)| used_temps.contains(n))
+            .map(|(n, tk_type)| (n, *tk_type))
+            .collect();
+        content.extend(body.lines());
+
+        let params = if function.params.is_empty() {
+            String::from("void")
+        } else {
+            function
+                .params
+                .iter()
+                .map(|param| match param.tk_type {
+                    Type::Literal => format!("const char *a_{}", param.name),
+                    tk_type => format!("{} {}", tk_type, var_name(&param.name)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let return_type = match function.return_type {
+            Some(return_type) => return_type.to_string(),
+            None => String::from("void"),
+        };
+
+        Unit {
+            header: format!("{} {}({})", return_type, function_name(&function.name), params),
+            temp_vars,
+            content,
+        }
+    }
+
+    // The C condition of the branch that starts at instruction i: the
+    // operand of an if_false, or the comparison that computes it when
+    // it is a temporary variable nothing else reads. The position of
+    // the if_false comes with it.
+    fn condition(function: &Function, live_out: &[HashSet<usize>], i: usize, end: usize) -> Option<(String, usize)> {
+        match &function.code[i] {
+            Instr::IfFalse(condition, _) => Some((c_operand(condition), i)),
+            Instr::BinOp(target @ Operand::Temp(n), left, operator, right, _)
+                if operator.is_relational() && i + 1 < end =>
+            {
+                match &function.code[i + 1] {
+                    Instr::IfFalse(condition, _) if condition == target && !live_out[i + 1].contains(n) => Some((
+                        format!("{} {} {}", c_operand(left), c_operator(*operator), c_operand(right)),
+                        i + 1,
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // The C statements of the instructions in range, depth levels
+    // deep. A label with a goto back to it later in the range starts
+    // a loop, and an if_false to a label later in the range starts a
+    // branch; an else part is jumped over at the end of the then part.
+    fn block(
+        &mut self,
+        function: &Function,
+        live_out: &[HashSet<usize>],
+        range: Range<usize>,
+        depth: usize,
+        loop_labels: Option<LoopLabels>,
+        body: &mut Body,
+    ) {
+        let code = &function.code;
+        let position = |instr: Instr, range: Range<usize>| range.into_iter().find(|i| code[*i] == instr);
+
+        let mut i = range.start;
+        while i < range.end {
+            // the writes that come one after the other are a single printf
+            let writes: Vec<(&Operand, Type)> = code[i..range.end]
+                .iter()
+                .map_while(|instr| match instr {
+                    Instr::Write(value, tk_type) => Some((value, *tk_type)),
+                    _ => None,
+                })
+                .collect();
+            if !writes.is_empty() {
+                body.line(depth, &self.printf(&writes));
+                i += writes.len();
+                continue;
+            }
+
+            // label begin ... goto begin, followed by the label the loop
+            // ends at, when there is one
+            if let Instr::Label(begin) = code[i] {
+                if let Some(last) = (i + 1..range.end).rev().find(|g| code[*g] == Instr::Goto(begin)) {
+                    let end = match code.get(last + 1) {
+                        Some(Instr::Label(end)) if last + 1 < range.end => Some(*end),
+                        _ => None,
+                    };
+                    body.label(begin);
+
+                    // a loop that starts with its test is a while
+                    let mut first = i + 1;
+                    let mut header = String::from("for (;;) {");
+                    if let (Some(end), Some((condition, k))) = (end, Self::condition(function, live_out, first, last)) {
+                        if matches!(code[k], Instr::IfFalse(_, label) if label == end) {
+                            header = format!("while ({}) {{", condition);
+                            if k > first {
+                                body.folded.insert(first);
+                            }
+                            first = k + 1;
+                        }
                     }
-                    TempVarType::Real => {
-                        writeln!(file, "    real T{}", i);
+                    body.line(depth, &header);
+                    let labels = LoopLabels { begin, end };
+                    self.block(function, live_out, first..last, depth + 1, Some(labels), body);
+                    body.line(depth, "}");
+                    match end {
+                        Some(end) => {
+                            body.label(end);
+                            i = last + 2;
+                        }
+                        None => i = last + 1,
                     }
+                    continue;
                 }
             }
-            writeln!(file, "    /*------------------------------*/");
+
+            // if_false condition goto else ... [goto end] label else ... [label end]
+            if let Some((condition, k)) = Self::condition(function, live_out, i, range.end) {
+                let else_label = match code[k] {
+                    Instr::IfFalse(_, label) => label,
+                    _ => unreachable!(),
+                };
+                if let Some(j) = position(Instr::Label(else_label), k + 1..range.end) {
+                    if k > i {
+                        body.folded.insert(i);
+                    }
+                    let end = match code[j - 1] {
+                        Instr::Goto(end) if j - 1 > k => position(Instr::Label(end), j + 1..range.end).map(|m| (end, m)),
+                        _ => None,
+                    };
+                    body.line(depth, &format!("if ({}) {{", condition));
+                    match end {
+                        Some((end, m)) => {
+                            self.block(function, live_out, k + 1..j - 1, depth + 1, loop_labels, body);
+                            body.line(depth, "} else {");
+                            body.label(else_label);
+                            self.block(function, live_out, j + 1..m, depth + 1, loop_labels, body);
+                            body.line(depth, "}");
+                            body.label(end);
+                            i = m + 1;
+                        }
+                        None => {
+                            self.block(function, live_out, k + 1..j, depth + 1, loop_labels, body);
+                            body.line(depth, "}");
+                            body.label(else_label);
+                            i = j + 1;
+                        }
+                    }
+                    continue;
+                }
+            }
+
+            // a jump out of this shape is a break or a continue when it
+            // goes to the end or to the beginning of the loop it is in
+            let jump = |body: &mut Body, label: usize| match loop_labels {
+                Some(labels) if labels.end == Some(label) => String::from("break;"),
+                Some(labels) if labels.begin == label => String::from("continue;"),
+                _ => body.goto(label),
+            };
+            match &code[i] {
+                Instr::Label(label) => body.label(*label),
+                Instr::Goto(label) => {
+                    let line = jump(body, *label);
+                    body.line(depth, &line);
+                }
+                Instr::IfFalse(condition, label) => {
+                    let line = format!("if (!{}) {}", c_operand(condition), jump(body, *label));
+                    body.line(depth, &line);
+                }
+                instr => {
+                    let line = self.statement(instr);
+                    body.line(depth, &line);
+                }
+            }
+            i += 1;
         }
+    }
 
-        for line in &self.content {
-            write!(file, "{}", line);
+    // the C statement of an instruction that is not a jump nor a label
+    fn statement(&mut self, instr: &Instr) -> String {
+        match instr {
+            // a literal is an array in C, so it is copied instead
+            Instr::Assign(target, value, Type::Literal) => {
+                self.use_helper(Helper::CopyLiteral);
+                format!("mgol_copia_literal({}, {});", c_operand(target), c_operand(value))
+            }
+            Instr::Assign(target, value, _) => format!("{} = {};", c_operand(target), c_operand(value)),
+            Instr::BinOp(target, left, operator, right, _) => format!(
+                "{} = {} {} {};",
+                c_operand(target),
+                c_operand(left),
+                c_operator(*operator),
+                c_operand(right)
+            ),
+            Instr::Read(target, tk_type) => match tk_type {
+                // a logico is read as the word verdadeiro or falso
+                Type::Logico => {
+                    self.use_helper(Helper::ReadLogico);
+                    format!("{} = mgol_leia_logico();", c_operand(target))
+                }
+                Type::Inteiro => format!("scanf(\"%d\", &{});", c_operand(target)),
+                Type::Real => format!("scanf(\"%lf\", &{});", c_operand(target)),
+                // a literal holds 255 characters and the '\0'
+                Type::Literal => format!("scanf(\"%255s\", {});", c_operand(target)),
+            },
+            Instr::Call(target, name, args) => {
+                let args: Vec<String> = args.iter().map(c_operand).collect();
+                match target {
+                    Some(target) => format!("{} = {}({});", c_operand(target), function_name(name), args.join(", ")),
+                    None => format!("{}({});", function_name(name), args.join(", ")),
+                }
+            }
+            Instr::Return(Some(value)) => format!("return {};", c_operand(value)),
+            Instr::Return(None) => String::from("return;"),
+            // the helper function stops the program when the index is
+            // out of bounds
+            Instr::CheckIndex(index, size, row) => {
+                self.use_helper(Helper::CheckedIndex);
+                format!("mgol_indice({}, {}, {});", c_operand(index), size, row)
+            }
+            Instr::Write(..) | Instr::Label(_) | Instr::Goto(_) | Instr::IfFalse(..) => unreachable!(),
+        }
+    }
+
+    fn printf(&self, writes: &[(&Operand, Type)]) -> String {
+        let mut format = String::new();
+        let mut values = Vec::new();
+        for (value, tk_type) in writes {
+            match (value, tk_type) {
+                // a literal goes straight into the format string, so a '%'
+                // in it must not be taken as a conversion specification
+                (Operand::Lit(text), _) => format.push_str(&c_string(text).replace('%', "%%")),
+                // a logico is written as the word verdadeiro or falso
+                (_, Type::Logico) => {
+                    format.push_str("%s");
+                    values.push(format!("{} ? \"verdadeiro\" : \"falso\"", c_operand(value)));
+                }
+                _ => {
+                    format.push_str(match tk_type {
+                        Type::Inteiro => "%d",
+                        Type::Real => "%lf",
+                        _ => "%s",
+                    });
+                    values.push(c_operand(value));
+                }
+            }
         }
 
-        writeln!(file, "    return 0;");
-        writeln!(file, "}}");
+        if values.is_empty() {
+            format!("printf(\"{}\");", format)
+        } else {
+            format!("printf(\"{}\", {});", format, values.join(", "))
+        }
+    }
+
+    fn write_file(&self, file: &mut File) -> io::Result<()> {
+        writeln!(file, "#include <stdio.h>")?;
+        if self.helpers.contains(&Helper::CheckedIndex) {
+            writeln!(file, "#include <stdlib.h>")?;
+        }
+        if self.helpers.contains(&Helper::ReadLogico) || self.helpers.contains(&Helper::CopyLiteral) {
+            writeln!(file, "#include <string.h>")?;
+        }
+        writeln!(file)?;
+        writeln!(file, "typedef char literal[256];")?;
+        writeln!(file, "typedef int inteiro;")?;
+        writeln!(file, "typedef double real;")?;
+        writeln!(file, "typedef int logico;")?;
+        writeln!(file)?;
+
+        for helper in &self.helpers {
+            match helper {
+                Helper::CheckedIndex => {
+                    writeln!(file, "int mgol_indice(int i, int n, int linha)")?;
+                    writeln!(file, "{{")?;
+                    writeln!(file, "    if (i < 0 || i >= n) {{")?;
+                    writeln!(
+                        file,
+                        "        printf(\"Erro: indice %d fora dos limites do vetor [0, %d) na linha %d\\n\", i, n, linha);"
+                    )?;
+                    writeln!(file, "        exit(1);")?;
+                    writeln!(file, "    }}")?;
+                    writeln!(file, "    return i;")?;
+                    writeln!(file, "}}")?;
+                }
+                Helper::ReadLogico => {
+                    writeln!(file, "logico mgol_leia_logico(void)")?;
+                    writeln!(file, "{{")?;
+                    writeln!(file, "    literal s;")?;
+                    writeln!(file, "    scanf(\"%255s\", s);")?;
+                    writeln!(file, "    return strcmp(s, \"verdadeiro\") == 0;")?;
+                    writeln!(file, "}}")?;
+                }
+                Helper::CopyLiteral => {
+                    writeln!(file, "void mgol_copia_literal(char *destino, const char *origem)")?;
+                    writeln!(file, "{{")?;
+                    writeln!(file, "    size_t n = strlen(origem);")?;
+                    writeln!(file, "    if (n > sizeof(literal) - 1) {{")?;
+                    writeln!(file, "        n = sizeof(literal) - 1;")?;
+                    writeln!(file, "    }}")?;
+                    writeln!(file, "    memmove(destino, origem, n);")?;
+                    writeln!(file, "    destino[n] = '\\0';")?;
+                    writeln!(file, "}}")?;
+                }
+            }
+            writeln!(file)?;
+        }
+
+        for line in &self.declarations {
+            write!(file, "{}", line)?;
+        }
+
+        for subprogram in &self.subprograms {
+            subprogram.write(file)?;
+            writeln!(file)?;
+        }
+
+        self.main.write(file)
+    }
+
+    // write the obj file to path
+    pub fn create(&self, path: &str) {
+        let mut file = match File::create(path) {
+            Err(_) => panic!("Não foi possível criar o código objeto {}", path),
+            Ok(file) => file,
+        };
+
+        if self.write_file(&mut file).is_err() {
+            panic!("Não foi possível escrever o código objeto {}", path);
+        }
     }
 }
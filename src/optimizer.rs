@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::fmt;
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// How many times each optimization changed the intermediate code.
+#[derive(Default, PartialEq, Debug)]
+pub struct Report {
+    pub folded_constants: usize,
+    pub propagated_copies: usize,
+    pub dead_temps: usize,
+    pub constant_branches: usize,
+    pub unreachable_code: usize,
+}
+
+impl Report {
+    fn total(&self) -> usize {
+        self.folded_constants
+            + self.propagated_copies
+            + self.dead_temps
+            + self.constant_branches
+            + self.unreachable_code
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total() == 0 {
+            return writeln!(f, "Nenhuma otimização foi aplicada");
+        }
+        writeln!(f, "Otimizações aplicadas:")?;
+        for (name, count) in [
+            ("dobramento de constantes", self.folded_constants),
+            ("propagação de cópias", self.propagated_copies),
+            ("eliminação de temporárias mortas", self.dead_temps),
+            ("remoção de desvios constantes", self.constant_branches),
+            ("remoção de código inalcançável", self.unreachable_code),
+        ] {
+            if count > 0 {
+                writeln!(f, "    {}: {}", name, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Optimize every function of the program, running the passes again
+// while one of them changes something, and report what was done.
+pub fn optimize(program: &mut Program) -> Report {
+    let mut report = Report::default();
+    let Program { globals, subprograms, main } = program;
+
+    for function in subprograms.iter_mut().chain(Some(main)) {
+        loop {
+            let before = report.total();
+            report.folded_constants += fold_constants(globals, function);
+            report.propagated_copies += coalesce_copies(globals, function);
+            report.propagated_copies += propagate_copies(globals, function);
+            report.constant_branches += remove_constant_branches(function);
+            report.unreachable_code += remove_unreachable_code(function);
+            report.dead_temps += remove_dead_temps(function);
+            if report.total() == before {
+                break;
+            }
+        }
+        compact_temps(function);
+    }
+
+    report
+}
+
+// the value of left operator right, when it is known now and the
+// same as at runtime
+fn fold(left: &Operand, operator: Operator, right: &Operand, tk_type: Type) -> Option<Operand> {
+    let number = |operand: &Operand| match operand {
+        Operand::Int(n) => Some(*n as f64),
+        Operand::Real(x) => Some(*x),
+        _ => None,
+    };
+
+    if operator.is_relational() {
+        let (left, right) = (number(left)?, number(right)?);
+        let value = match operator {
+            Operator::Lt => left < right,
+            Operator::Le => left <= right,
+            Operator::Gt => left > right,
+            Operator::Ge => left >= right,
+            Operator::Eq => left == right,
+            Operator::Ne => left != right,
+            Operator::And => left != 0.0 && right != 0.0,
+            _ => left != 0.0 || right != 0.0,
+        };
+        return Some(Operand::Int(value as i32));
+    }
+
+    match (left, right, tk_type) {
+        // an overflow or a division by zero is left to the runtime
+        (Operand::Int(left), Operand::Int(right), Type::Inteiro) => {
+            let value = match operator {
+                Operator::Add => left.checked_add(*right),
+                Operator::Sub => left.checked_sub(*right),
+                Operator::Mul => left.checked_mul(*right),
+                _ => left.checked_div(*right),
+            };
+            value.map(Operand::Int)
+        }
+        (_, _, Type::Real) => {
+            let (left, right) = (number(left)?, number(right)?);
+            let value = match operator {
+                Operator::Add => left + right,
+                Operator::Sub => left - right,
+                Operator::Mul => left * right,
+                _ => left / right,
+            };
+            Some(Operand::Real(value)).filter(|_| value.is_finite())
+        }
+        _ => None,
+    }
+}
+
+// T0 = 2 + 3 becomes T0 = 5
+fn fold_constants(globals: &[Var], function: &mut Function) -> usize {
+    let mut count = 0;
+    for i in 0..function.code.len() {
+        if let Instr::BinOp(target, left, operator, right, tk_type) = &function.code[i] {
+            if let Some(value) = fold(left, *operator, right, *tk_type) {
+                let target_type = function.type_of(globals, target);
+                function.code[i] = Instr::Assign(target.clone(), value, target_type);
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// After x = y (or x = 5), a read of x up to the next label, or up to
+// a change of x or of y, reads y (or 5) instead. A call may change any
+// global variable, so nothing is kept across it.
+fn propagate_copies(globals: &[Var], function: &mut Function) -> usize {
+    let mut count = 0;
+    // (copy, value) pairs, where the copy is a variable or a temporary
+    let mut copies: Vec<(Operand, Operand)> = Vec::new();
+
+    for i in 0..function.code.len() {
+        if let Instr::Label(_) = function.code[i] {
+            copies.clear();
+        }
+
+        for slot in function.code[i].uses_mut() {
+            if let Some((_, value)) = copies.iter().find(|(copy, _)| copy == slot) {
+                *slot = value.clone();
+                count += 1;
+            }
+        }
+
+        let instr = &function.code[i];
+        if let Instr::Call(..) = instr {
+            copies.clear();
+        }
+        if let Some(changed @ (Operand::Var(_) | Operand::Temp(_))) = instr.target() {
+            copies.retain(|(copy, value)| copy != changed && value != changed);
+        }
+        // a literal is copied by value, so only the other types are
+        // kept; a logico constant is 1 or 0
+        if let Instr::Assign(target @ (Operand::Var(_) | Operand::Temp(_)), value, tk_type) = instr {
+            let copy = match value {
+                Operand::Int(_) | Operand::Real(_) => true,
+                Operand::Var(_) | Operand::Temp(_) => value != target,
+                _ => false,
+            };
+            let same_type = match (function.type_of(globals, value), tk_type) {
+                (Type::Inteiro, Type::Logico) => matches!(value, Operand::Int(_)),
+                (value_type, tk_type) => value_type == *tk_type,
+            };
+            if copy && same_type && *tk_type != Type::Literal {
+                copies.push((target.clone(), value.clone()));
+            }
+        }
+    }
+    count
+}
+
+// how many times each temporary variable is read and written
+fn temp_counts(function: &Function) -> (Vec<usize>, Vec<usize>) {
+    let mut reads = vec![0; function.temps.len()];
+    let mut writes = vec![0; function.temps.len()];
+    for instr in &function.code {
+        for operand in instr.uses() {
+            if let Some(n) = operand.temp() {
+                reads[n] += 1;
+            }
+        }
+        if let Some(n) = instr.target().and_then(Operand::temp) {
+            writes[n] += 1;
+        }
+    }
+    (reads, writes)
+}
+
+// T0 = C + 2.0 followed by C = T0, when T0 is used nowhere else,
+// becomes C = C + 2.0
+fn coalesce_copies(globals: &[Var], function: &mut Function) -> usize {
+    let mut count = 0;
+    let (reads, writes) = temp_counts(function);
+
+    let mut i = 0;
+    while i + 1 < function.code.len() {
+        let temp = function.code[i].target().and_then(Operand::temp);
+        if let (Some(n), Instr::Assign(target, Operand::Temp(value), tk_type)) = (temp, &function.code[i + 1]) {
+            let same = n == *value
+                && reads[n] == 1
+                && writes[n] == 1
+                && *tk_type != Type::Literal
+                && function.temps[n] == function.type_of(globals, target);
+            if same && !matches!(function.code[i], Instr::Read(..)) {
+                let target = target.clone();
+                *function.code[i].target_mut().unwrap() = target;
+                function.code.remove(i + 1);
+                count += 1;
+            }
+        }
+        i += 1;
+    }
+    count
+}
+
+// if_false 1 goto L is removed and if_false 0 goto L becomes goto L
+fn remove_constant_branches(function: &mut Function) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < function.code.len() {
+        match function.code[i] {
+            Instr::IfFalse(Operand::Int(n), _) if n != 0 => {
+                function.code.remove(i);
+                count += 1;
+                continue;
+            }
+            Instr::IfFalse(Operand::Int(_), label) => {
+                function.code[i] = Instr::Goto(label);
+                count += 1;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    count
+}
+
+// The code after a goto or a return, up to a label some goto uses, is
+// never run. A label no goto uses is removed too, and so is a goto to
+// the label that follows it.
+fn remove_unreachable_code(function: &mut Function) -> usize {
+    let mut count = 0;
+    loop {
+        let used_labels: HashSet<usize> = function
+            .code
+            .iter()
+            .filter_map(|instr| match instr {
+                Instr::Goto(label) | Instr::IfFalse(_, label) => Some(*label),
+                _ => None,
+            })
+            .collect();
+
+        let len = function.code.len();
+        let mut code = Vec::new();
+        let mut reachable = true;
+        let mut removed = 0;
+        for instr in function.code.drain(..) {
+            match instr {
+                Instr::Label(label) if !used_labels.contains(&label) => continue,
+                Instr::Label(_) => reachable = true,
+                _ if !reachable => {
+                    removed += 1;
+                    continue;
+                }
+                _ => (),
+            }
+            if let (Some(Instr::Goto(label)), Instr::Label(next)) = (code.last(), &instr) {
+                if label == next {
+                    code.pop();
+                }
+            }
+            if let Instr::Goto(_) | Instr::Return(_) = instr {
+                reachable = false;
+            }
+            code.push(instr);
+        }
+        // a label may have lost its last goto, so it goes again
+        let changed = code.len() + removed != len;
+        function.code = code;
+        count += removed;
+        if removed == 0 && !changed {
+            break;
+        }
+    }
+    count
+}
+
+// An instruction that only writes a temporary variable nobody reads is
+// removed; a call is kept, without its target.
+fn remove_dead_temps(function: &mut Function) -> usize {
+    let mut count = 0;
+    let (reads, _) = temp_counts(function);
+
+    let mut i = 0;
+    while i < function.code.len() {
+        let dead = function.code[i]
+            .target()
+            .and_then(Operand::temp)
+            .is_some_and(|n| reads[n] == 0);
+        match &mut function.code[i] {
+            Instr::Call(target, ..) if dead => {
+                *target = None;
+                count += 1;
+            }
+            Instr::Assign(..) | Instr::BinOp(..) if dead => {
+                function.code.remove(i);
+                count += 1;
+                continue;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    count
+}
+
+// the temporary variables still in use are numbered again from T0
+fn compact_temps(function: &mut Function) {
+    let (reads, writes) = temp_counts(function);
+    let mut numbers = Vec::new();
+    let mut temps = Vec::new();
+    for (n, tk_type) in function.temps.iter().enumerate() {
+        numbers.push(temps.len());
+        if reads[n] + writes[n] > 0 {
+            temps.push(*tk_type);
+        }
+    }
+    function.renumber_temps(&numbers, temps);
+}
@@ -1,6 +1,8 @@
+use super::backend::Backend;
 use super::grammar::Grammar;
-use super::obj_file::{ObjFile, TempVarType};
+use super::ir::{Operand, Operator, Type};
 use super::scanner::Scanner;
+use super::symbol_table::Subprogram;
 use super::slr_table::{ActionTable, GotoTable, SlrAction};
 use super::token::Token;
 
@@ -28,9 +30,11 @@ impl SyntaticStack {
     }
 }
 
+// The value items (OPRD, LD, ARG, EXP_R and VCTE) keep the operand
+// of their value instead of a lexeme.
 #[derive(Debug)]
 struct SemanticStack {
-    stack: Vec<(String, String, String)>, // Vec<(Item, lexeme, tk_type)>
+    stack: Vec<(String, String, String, Option<Operand>)>, // Vec<(Item, lexeme, tk_type, operand)>
 }
 
 impl SemanticStack {
@@ -38,16 +42,30 @@ impl SemanticStack {
         SemanticStack {
             // a never empty stack: to not panic in top function,
             // we make sure that stack.len() > 0
-            stack: vec![(String::new(), String::new(), String::new())],
+            stack: vec![(String::new(), String::new(), String::new(), None)],
         }
     }
 
-    fn top(&self) -> &(String, String, String) {
-        &self.stack[self.stack.len() - 1]
+    fn top(&self) -> (&String, &String, &String) {
+        let (item, lexeme, tk_type, _) = &self.stack[self.stack.len() - 1];
+        (item, lexeme, tk_type)
     }
 
     fn push(&mut self, attrs: (String, String, String)) {
-        self.stack.push(attrs);
+        let (item, lexeme, tk_type) = attrs;
+        self.stack.push((item, lexeme, tk_type, None));
+    }
+
+    fn push_value(&mut self, item: &str, operand: Operand, tk_type: String) {
+        self.stack.push((String::from(item), String::new(), tk_type, Some(operand)));
+    }
+
+    // Take the operand and the tk_type of the value item on the top. An
+    // entry without an operand is only found after a syntactic error,
+    // when the program is not used.
+    fn pop_value(&mut self) -> (Operand, String) {
+        let (_, _, tk_type, operand) = self.stack.pop().unwrap();
+        (operand.unwrap_or(Operand::Int(0)), tk_type)
     }
 
     fn pop(&mut self, count: u8) {
@@ -55,6 +73,51 @@ impl SemanticStack {
             self.stack.pop();
         }
     }
+
+    // Make the entry on the top the first item of a new list. A list is
+    // kept as its items followed by (Item, number of items, "").
+    fn new_list(&mut self, list: &str) {
+        self.push((String::from(list), String::from("1"), String::new()));
+    }
+
+    // move the entry on the top to the end of the list right below it
+    fn append_to_list(&mut self) {
+        let item = self.stack.pop().unwrap();
+        let (list, n, _) = self.top();
+        let list = list.clone();
+        let n = n.parse::<usize>().unwrap_or(0);
+        self.stack.pop();
+
+        self.stack.push(item);
+        self.push((list, (n + 1).to_string(), String::new()));
+    }
+
+    // take the items of the list on the top
+    fn pop_entries(&mut self) -> Vec<(String, String, String, Option<Operand>)> {
+        let (_, n, _) = self.top();
+        let n = n.parse::<usize>().unwrap_or(0);
+        self.stack.pop();
+
+        // the first entry is never removed
+        let n = n.min(self.stack.len() - 1);
+        self.stack.split_off(self.stack.len() - n)
+    }
+
+    // take the list on the top as (lexeme, tk_type) pairs
+    fn pop_list(&mut self) -> Vec<(String, String)> {
+        self.pop_entries()
+            .into_iter()
+            .map(|(_, lexeme, tk_type, _)| (lexeme, tk_type))
+            .collect()
+    }
+
+    // take the list of values on the top as (operand, tk_type) pairs
+    fn pop_value_list(&mut self) -> Vec<(Operand, String)> {
+        self.pop_entries()
+            .into_iter()
+            .map(|(_, _, tk_type, operand)| (operand.unwrap_or(Operand::Int(0)), tk_type))
+            .collect()
+    }
 }
 
 pub struct Parser {
@@ -65,6 +128,19 @@ pub struct Parser {
     token_buffer: Vec<Token>,
     syntatic_error_msgs: Vec<String>,
     semantic_error_msgs: Vec<String>,
+    // check the array indexes at runtime
+    bounds_check: bool,
+    // show each reduction of the syntactic analysis
+    show_reductions: bool,
+    // name and return type (None for a procedure) of the subprogram
+    // being analysed, if any
+    subprogram: Option<(String, Option<String>)>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Parser {
@@ -77,16 +153,30 @@ impl Parser {
             token_buffer: Vec::new(),
             syntatic_error_msgs: Vec::new(),
             semantic_error_msgs: Vec::new(),
+            bounds_check: false,
+            show_reductions: true,
+            subprogram: None,
         }
     }
 
-    pub fn parse(&mut self, scanner: &mut Scanner) {
-        let mut obj_file = ObjFile::new();
+    // emit code that checks the array indexes at runtime
+    pub fn set_bounds_check(&mut self, bounds_check: bool) {
+        self.bounds_check = bounds_check;
+    }
+
+    // the reductions are shown unless the program is going to run
+    pub fn set_show_reductions(&mut self, show_reductions: bool) {
+        self.show_reductions = show_reductions;
+    }
+
+    // Analyse the source code, giving the program to the backend. The
+    // errors found are shown and true is returned when there are none,
+    // that is, when the backend got the whole program.
+    pub fn parse(&mut self, scanner: &mut Scanner, backend: &mut dyn Backend) -> bool {
         let mut semantic_stack = SemanticStack::new();
 
         let mut token = self.next_token(scanner);
         let mut last_token = Token::new(String::new(), None, None);
-        let mut last_seen_operator = String::new();
         let mut a = token.class.clone();
         loop {
             let s = self.syntatic_stack.top();
@@ -94,31 +184,31 @@ impl Parser {
             match action {
                 SlrAction::S(t) => {
                     self.syntatic_stack.push(t);
-                    last_token = token;
-                    token = self.next_token(scanner);
-                    if String::from("id").eq(&token.class) {
+                    // an 'id' or an operator goes into the semantic stack when
+                    // it is shifted, not when it is seen as a lookahead: a
+                    // reduction may still happen before it is actually consumed
+                    if ["id", "opr", "opm"].contains(&token.class.as_str()) {
                         semantic_stack.push((
-                            String::from("id"),
+                            token.class.clone(),
                             token.lexeme.as_ref().unwrap().clone(),
                             String::new(),
                         ));
                     }
-                    if String::from("opr").eq(&token.class) || String::from("opm").eq(&token.class)
-                    {
-                        last_seen_operator = token.lexeme.as_ref().unwrap().clone();
-                    }
+                    last_token = token;
+                    token = self.next_token(scanner);
                     a = token.class.clone();
                 }
                 SlrAction::R(r) => {
                     let rule = self.grammar.get_rule(r as usize);
-                    rule.show();
+                    if self.show_reductions {
+                        rule.show();
+                    }
                     self.run_semantic_rule(
                         r,
                         scanner,
-                        &mut obj_file,
+                        backend,
                         &mut semantic_stack,
                         &last_token,
-                        last_seen_operator.clone(),
                     );
                     #[allow(non_snake_case)]
                     let A = rule.left;
@@ -129,6 +219,17 @@ impl Parser {
                         .push(self.goto_table.get(&(t, A.text.clone())));
                 }
                 SlrAction::Acc => break,
+                SlrAction::E(_) if String::from("EOF").eq(&token.class) => {
+                    // nothing can be recovered at the end of the file: removing
+                    // the EOF gives the EOF again, and a missing token would only
+                    // put off the error
+                    self.syntatic_error_msgs.push(format!(
+                        "[ES42] Erro sintático na linha {}, coluna {}: fim do arquivo inesperado, o programa está incompleto\n    NOTA: verifique se algum comentário ou literal não foi fechado e se o programa termina com 'fim'",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                    break;
+                }
                 SlrAction::E(e) => {
                     // put the last read Token back into the input
                     self.token_buffer.push(token);
@@ -150,16 +251,14 @@ impl Parser {
         let syntatic_errors = self.show_syntatic_error_msgs();
         let semantic_errors = self.show_semantic_error_msgs();
 
-        if lexical_errors + syntatic_errors + semantic_errors == 0 {
-            obj_file.create();
-        }
+        lexical_errors + syntatic_errors + semantic_errors == 0
     }
 
     fn next_token(&mut self, scanner: &mut Scanner) -> Token {
         if self.token_buffer.is_empty() {
-            return scanner.safe_scan();
+            scanner.safe_scan()
         } else {
-            return self.token_buffer.pop().unwrap();
+            self.token_buffer.pop().unwrap()
         }
     }
 
@@ -167,38 +266,82 @@ impl Parser {
         &mut self,
         r: u8,
         scanner: &mut Scanner,
-        obj_file: &mut ObjFile,
+        backend: &mut dyn Backend,
         semantic_stack: &mut SemanticStack,
         token: &Token,
-        last_seen_operator: String,
     ) {
-        static mut TEMP_VAR: u8 = 0;
-
         // println!(">>> {}", r);
         // println!("{:?}", semantic_stack);
 
         match r {
             5 => {
-                obj_file.print(format!("\n"));
+                backend.end_declarations();
             }
             6 => {
-                // print ';\n' in the obj file
-                obj_file.print(String::from(";\n"));
+                // take L.lexeme and L.tk_type from the semantic stack
+                let (_, lexeme, tk_type) = semantic_stack.top();
+                let lexeme = lexeme.clone();
+                let tk_type = tk_type.clone();
+                semantic_stack.pop(1);
+
+                // an array has its size in its type: inteiro[10]
+                match array_type(&tk_type) {
+                    Some((tk_type, size)) => backend.declare_variable(&lexeme, ir_type(&tk_type), Some(size)),
+                    None => backend.declare_variable(&lexeme, ir_type(&tk_type), None),
+                }
             }
-            7 => {
+            7 | 71 => {
+                // remove id from the stack (its lexeme comes from the last token)
+                semantic_stack.pop(1);
+
                 // take TIPO.tk_type from the semantic stack
                 let (_, _, tk_type) = semantic_stack.top();
                 let tk_type = tk_type.clone();
-                semantic_stack.pop(2);
+                semantic_stack.pop(1);
 
                 // id.t_type = TIPO.type
-                let mut id = Token::new_from_ref(token);
-                id.tk_type = Some(tk_type);
                 let lexeme = token.lexeme.as_ref().unwrap().clone();
-                scanner.symbol_table.update(lexeme.clone(), id);
+                self.declare_variable(scanner, lexeme.clone(), tk_type.clone(), None);
 
-                // print the id.lexeme in the obj file
-                obj_file.print(lexeme);
+                let item = if r == 7 { "L" } else { "PAR" };
+                semantic_stack.push((String::from(item), lexeme, tk_type));
+            }
+            95 => {
+                // take OPRD.operand and OPRD.tk_type (the array size) from the semantic stack
+                let (size, size_tk_type) = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
+                let (_, lexeme, _) = semantic_stack.top();
+                let lexeme = lexeme.clone();
+                semantic_stack.pop(1);
+
+                // take TIPO.tk_type from the semantic stack
+                let (_, _, tk_type) = semantic_stack.top();
+                let tk_type = tk_type.clone();
+                semantic_stack.pop(1);
+
+                let size = match size {
+                    Operand::Int(n) if n > 0 && String::from("inteiro").eq(&size_tk_type) => n as usize,
+                    _ => {
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe22] Erro: O tamanho do vetor '{}' deve ser um número inteiro positivo na linha {}, coluna {}",
+                            lexeme,
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ));
+                        1
+                    }
+                };
+
+                // the array type keeps its size: inteiro[10]
+                self.declare_variable(
+                    scanner,
+                    lexeme.clone(),
+                    format!("{}[{}]", tk_type, size),
+                    None,
+                );
+
+                semantic_stack.push((String::from("L"), lexeme, format!("{}[{}]", tk_type, size)));
             }
             8 => {
                 let token = scanner.symbol_table.get(String::from("inteiro")).unwrap();
@@ -207,8 +350,6 @@ impl Parser {
                     String::from(""),
                     token.tk_type.unwrap(),
                 ));
-                let (_, _, tk_type) = semantic_stack.top();
-                obj_file.print(format!("    {} ", tk_type.clone()));
             }
             9 => {
                 let token = scanner.symbol_table.get(String::from("real")).unwrap();
@@ -217,8 +358,6 @@ impl Parser {
                     String::from(""),
                     token.tk_type.unwrap(),
                 ));
-                let (_, _, tk_type) = semantic_stack.top();
-                obj_file.print(format!("    {} ", tk_type.clone()));
             }
             10 => {
                 let token = scanner.symbol_table.get(String::from("literal")).unwrap();
@@ -227,33 +366,21 @@ impl Parser {
                     String::from(""),
                     token.tk_type.unwrap(),
                 ));
-                let (_, _, tk_type) = semantic_stack.top();
-                obj_file.print(format!("    {} ", tk_type.clone()));
             }
             12 => {
                 // take id.lexeme from the semantic stack
                 let (_, lexeme, _) = semantic_stack.top();
                 let lexeme = lexeme.clone();
                 semantic_stack.pop(1);
-                let id = scanner.symbol_table.get(lexeme).unwrap();
+                let id = scanner.symbol_table.get(lexeme.clone()).unwrap();
 
-                if id.tk_type != None {
-                    if String::from("inteiro").eq(id.tk_type.as_ref().unwrap()) {
-                        obj_file.print(format!(
-                            "    scanf(\"%d\", &{});\n",
-                            id.lexeme.as_ref().unwrap()
-                        ));
-                    } else if String::from("real").eq(id.tk_type.as_ref().unwrap()) {
-                        obj_file.print(format!(
-                            "    scanf(\"%lf\", &{});\n",
-                            id.lexeme.as_ref().unwrap()
-                        ));
+                if let Some(tk_type) = &id.tk_type {
+                    if array_type(tk_type).is_some() {
+                        self.array_without_index(scanner, &lexeme);
+                    } else if id.constant.is_some() {
+                        self.constant_changed(scanner, &lexeme);
                     } else {
-                        // else if String::from("literal").eq(id.tk_type.as_ref().unwrap())
-                        obj_file.print(format!(
-                            "    scanf(\"%s\", {});\n",
-                            id.lexeme.as_ref().unwrap()
-                        ));
+                        backend.read(Operand::Var(lexeme), ir_type(tk_type));
                     }
                 } else {
                     self.semantic_error_msgs.push(format!(
@@ -265,81 +392,52 @@ impl Parser {
                 }
             }
             13 => {
-                // take ARG.lexeme from the semantic stack
-                let (_, lexeme, _) = semantic_stack.top();
-                let lexeme = lexeme.clone();
-                semantic_stack.pop(1);
-                obj_file.print(format!("    printf({});\n", lexeme));
+                // take the ARG.operand and ARG.tk_type of each LARG item from the semantic stack
+                let args = semantic_stack.pop_value_list();
+                backend.write(
+                    args.into_iter()
+                        .map(|(operand, tk_type)| (operand, ir_type(&tk_type)))
+                        .collect(),
+                );
             }
             14 => {
-                let lit = Token::new_from_ref(token);
-                semantic_stack.push((
-                    String::from("ARG"),
-                    lit.lexeme.unwrap(),
-                    lit.tk_type.unwrap(),
-                ));
+                semantic_stack.push_value(
+                    "ARG",
+                    literal(token.lexeme.as_ref().unwrap()),
+                    token.tk_type.as_ref().unwrap().clone(),
+                );
             }
             15 => {
-                let num = Token::new_from_ref(token);
-                let tk_type = num.tk_type.as_ref().unwrap().clone();
-                let mut _lexeme = String::new();
-                if String::from("inteiro").eq(&tk_type) {
-                    _lexeme = format!("\"%d\", {}", num.lexeme.unwrap());
-                } else {
-                    // String::from("real").eq(&tk_type)
-                    _lexeme = format!("\"%lf\", {}", num.lexeme.unwrap());
-                }
-                semantic_stack.push((String::from("ARG"), _lexeme, num.tk_type.unwrap()));
+                // take LD.operand and LD.tk_type from the semantic stack
+                let (operand, tk_type) = semantic_stack.pop_value();
+                semantic_stack.push_value("ARG", operand, tk_type);
             }
             16 => {
-                let id = Token::new_from_ref(token);
-                let lexeme = id.lexeme.as_ref().unwrap().clone();
-                let id_tk_type = scanner.symbol_table.get(lexeme).unwrap().tk_type;
-
-                if id_tk_type != None {
-                    let mut _lexeme = String::new();
-                    if String::from("inteiro").eq(id_tk_type.as_ref().unwrap()) {
-                        _lexeme = format!("\"%d\", {}", id.lexeme.unwrap());
-                    } else if String::from("real").eq(id_tk_type.as_ref().unwrap()) {
-                        _lexeme = format!("\"%lf\", {}", id.lexeme.unwrap());
-                    } else {
-                        // else if String::from("literal").eq(id_tk_type.as_ref().unwrap())
-                        _lexeme = format!("\"%s\", {}", id.lexeme.unwrap());
-                    }
-                    semantic_stack.push((String::from("ARG"), _lexeme, id.tk_type.unwrap()));
-                } else {
-                    self.semantic_error_msgs.push(format!(
-                        "[ESe2] Erro: Variável '{}' não declarada na linha {}, coluna {}",
-                        id.lexeme.as_ref().unwrap(),
-                        scanner.get_row(),
-                        scanner.get_col()
-                    ));
-                }
+                semantic_stack.new_list("LARG");
             }
             18 => {
-                // take ld.lexeme from the semantic stack
-                let (_, ld_lexeme, ld_tk_type) = semantic_stack.top();
-                let ld_lexeme = ld_lexeme.clone();
-                let ld_tk_type = ld_tk_type.clone();
-                semantic_stack.pop(1);
+                // take LD.operand and LD.tk_type from the semantic stack
+                let (ld_operand, ld_tk_type) = semantic_stack.pop_value();
 
                 // take id.lexeme from the semantic stack
                 let (_, id_lexeme, _) = semantic_stack.top();
                 let id_lexeme = id_lexeme.clone();
                 semantic_stack.pop(1);
-                println!("Imhere{}", id_lexeme);
                 let id = scanner.symbol_table.get(id_lexeme).unwrap();
 
-                if id.tk_type != None {
-                    if ld_tk_type.eq(id.tk_type.as_ref().unwrap()) {
-                        obj_file.print(format!(
-                            "    {} = {};\n",
-                            id.lexeme.as_ref().unwrap(),
-                            ld_lexeme
-                        ));
-                    } else {
-                        println!("### {}, {}", id.lexeme.as_ref().unwrap(), ld_lexeme);
-                        println!("### {}, {}", id.tk_type.as_ref().unwrap(), ld_tk_type);
+                if let Some(id_tk_type) = &id.tk_type {
+                    if array_type(id_tk_type).is_some() {
+                        self.array_without_index(scanner, id.lexeme.as_ref().unwrap());
+                    } else if id.constant.is_some() {
+                        self.constant_changed(scanner, id.lexeme.as_ref().unwrap());
+                    } else if ld_tk_type.eq(id_tk_type) {
+                        backend.assign(
+                            Operand::Var(id.lexeme.as_ref().unwrap().clone()),
+                            ld_operand,
+                            ir_type(id_tk_type),
+                        );
+                    } else if !ld_tk_type.is_empty() {
+                        // an empty tk_type means the error was already reported by LD
                         self.semantic_error_msgs.push(format!(
                             "[ESe3] Erro: Tipos diferentes para atribuição na linha {}, coluna {}",
                             scanner.get_row(),
@@ -356,48 +454,30 @@ impl Parser {
                 }
             }
             19 => {
-                // take OPRD.lexeme and OPRD.tk_type from the semantic stack twice
-                let (_, lexeme, tk_type) = semantic_stack.top();
-                let lexeme1 = lexeme.clone();
-                let tk_type1 = tk_type.clone();
-                semantic_stack.pop(1);
+                // take OPRD.operand and OPRD.tk_type of both operands, with the
+                // operator between them, from the semantic stack
+                let (operand1, tk_type1) = semantic_stack.pop_value();
 
-                let (_, lexeme, tk_type) = semantic_stack.top();
-                let lexeme2 = lexeme.clone();
-                let tk_type2 = tk_type.clone();
+                // take opm.lexeme from the semantic stack
+                let (_, operator, _) = semantic_stack.top();
+                let operator = operator.clone();
                 semantic_stack.pop(1);
 
-                if tk_type1.eq(&tk_type2) && String::from("literal").ne(&tk_type1) {
-                    let mut _temp_var = 0;
-                    unsafe {
-                        _temp_var = TEMP_VAR;
-                        TEMP_VAR += 1;
-                    }
-                    println!("IMHERE");
-                    semantic_stack.push((String::from("LD"), format!("T{}", _temp_var), tk_type1.clone()));
-
-                    if String::from("inteiro").eq(&tk_type1) {
-                        obj_file.add_temp_var(TempVarType::Int);
-                    } else {
-                        obj_file.add_temp_var(TempVarType::Real);
-                    }
-                    obj_file.print(format!(
-                        "    T{} = {} {} {};\n",
-                        _temp_var, lexeme2, last_seen_operator, lexeme1
-                    ));
+                let (operand2, tk_type2) = semantic_stack.pop_value();
+
+                if tk_type1.is_empty() || tk_type2.is_empty() {
+                    // the error was already reported by OPRD
+                    semantic_stack.push_value("LDN", operand2, String::new());
+                } else if tk_type1.eq(&tk_type2)
+                    && String::from("literal").ne(&tk_type1)
+                    && String::from("logico").ne(&tk_type1)
+                {
+                    let temp_var = backend.new_temp(ir_type(&tk_type1));
+                    backend.binary_op(temp_var.clone(), operand2, operator_of(&operator), operand1);
+                    semantic_stack.push_value("LDN", temp_var, tk_type1);
                 } else {
-                    // put OPRDs back in the semantic stack
-                    semantic_stack.push((
-                        String::from("OPRD"),
-                        lexeme2,
-                        tk_type2,
-                    ));
-
-                    semantic_stack.push((
-                        String::from("OPRD"),
-                        lexeme1,
-                        tk_type1,
-                    ));
+                    // keep the semantic stack balanced with an untyped LDN
+                    semantic_stack.push_value("LDN", operand2, String::new());
 
                     self.semantic_error_msgs.push(format!(
                         "[ESe4] Erro: Operandos com tipos incompatíveis na linha {}, coluna {}",
@@ -407,28 +487,40 @@ impl Parser {
                 }
             }
             20 => {
-                // take OPRD.lexeme and OPRD.tk_type from the semantic stack once
-                let (_, lexeme, tk_type) = semantic_stack.top();
-                let lexeme = lexeme.clone();
-                let tk_type = tk_type.clone();
-                semantic_stack.pop(1);
-
-                semantic_stack.push((String::from("LD"), lexeme, tk_type));
+                // take OPRD.operand and OPRD.tk_type from the semantic stack once
+                let (operand, tk_type) = semantic_stack.pop_value();
+                semantic_stack.push_value("LDN", operand, tk_type);
             }
             21 => {
                 // take id.lexeme from the semantic stack
                 let (_, lexeme, _) = semantic_stack.top();
                 let lexeme = lexeme.clone();
                 semantic_stack.pop(1);
-                let id = scanner.symbol_table.get(lexeme).unwrap();
+                let id = scanner.symbol_table.get(lexeme.clone()).unwrap();
+
+                if scanner.symbol_table.get_subprogram(&lexeme).is_some() {
+                    // keep the semantic stack balanced with an untyped OPRD
+                    semantic_stack.push_value("OPRD", Operand::Var(lexeme.clone()), String::new());
 
-                if id.tk_type != None {
-                    semantic_stack.push((
-                        String::from("OPRD"),
-                        token.lexeme.as_ref().unwrap().clone(),
-                        token.tk_type.as_ref().unwrap().clone(),
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe8] Erro: Subprograma '{}' usado como variável na linha {}, coluna {}",
+                        lexeme,
+                        scanner.get_row(),
+                        scanner.get_col()
                     ));
+                } else if id.tk_type.as_deref().and_then(array_type).is_some() {
+                    // keep the semantic stack balanced with an untyped OPRD
+                    semantic_stack.push_value("OPRD", Operand::Var(lexeme.clone()), String::new());
+                    self.array_without_index(scanner, &lexeme);
+                } else if let (Some(value), Some(tk_type)) = (&id.constant, &id.tk_type) {
+                    // a named constant is replaced by its value
+                    semantic_stack.push_value("OPRD", value.clone(), tk_type.clone());
+                } else if let Some(tk_type) = &id.tk_type {
+                    semantic_stack.push_value("OPRD", Operand::Var(lexeme.clone()), tk_type.clone());
                 } else {
+                    // keep the semantic stack balanced with an untyped OPRD
+                    semantic_stack.push_value("OPRD", Operand::Var(lexeme.clone()), String::new());
+
                     self.semantic_error_msgs.push(format!(
                         "[ESe2] Erro: Variável '{}' não declarada na linha {}, coluna {}",
                         id.lexeme.as_ref().unwrap(),
@@ -438,87 +530,747 @@ impl Parser {
                 }
             }
             22 => {
-                semantic_stack.push((
-                    String::from("OPRD"),
-                    token.lexeme.as_ref().unwrap().clone(),
-                    token.tk_type.as_ref().unwrap().clone(),
-                ));
+                let operand = self.number(scanner, token);
+                semantic_stack.push_value("OPRD", operand, token.tk_type.as_ref().unwrap().clone());
             }
             24 => {
-                obj_file.print(format!("    }}\n"));
+                backend.if_end();
             }
             25 => {
-                // remove Tx_expr from the stack
+                // take EXP_R.operand from the semantic stack
+                let (condition, _) = semantic_stack.pop_value();
+                backend.if_begin(condition);
+            }
+            26 => {
+                // take LD.operand and LD.tk_type from the semantic stack
+                let (operand, tk_type) = semantic_stack.pop_value();
+
+                // an empty tk_type means the error was already reported by LD
+                if !tk_type.is_empty() && String::from("logico").ne(&tk_type) {
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe23] Erro: A condição deve ser do tipo logico na linha {}, coluna {}",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                }
+
+                semantic_stack.push_value("EXP_R", operand, tk_type);
+            }
+            101 => {
+                // take OPRD.operand and OPRD.tk_type of both operands, with the
+                // operator between them, from the semantic stack
+                let (operand1, tk_type1) = semantic_stack.pop_value();
+
+                // take opr.lexeme from the semantic stack
+                let (_, operator, _) = semantic_stack.top();
+                let operator = operator.clone();
+                semantic_stack.pop(1);
+
+                let (operand2, tk_type2) = semantic_stack.pop_value();
+
+                // numbers are compared with numbers and a logico only with
+                // another logico; an empty tk_type means the error was
+                // already reported by OPRD
+                let numeric = |tk_type: &String| {
+                    String::from("inteiro").eq(tk_type) || String::from("real").eq(tk_type)
+                };
+                let untyped = tk_type1.is_empty() || tk_type2.is_empty();
+                let comparable = (numeric(&tk_type1) && numeric(&tk_type2))
+                    || (String::from("logico").eq(&tk_type1)
+                        && String::from("logico").eq(&tk_type2)
+                        && (operator.eq("=") || operator.eq("<>")));
+
+                if comparable {
+                    let temp_var = backend.new_temp(Type::Logico);
+                    backend.binary_op(temp_var.clone(), operand2, operator_of(&operator), operand1);
+
+                    semantic_stack.push_value("LDN", temp_var, String::from("logico"));
+                } else {
+                    if !untyped {
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe5] Erro: Operandos com tipos incompatíveis para comparação na linha {}, coluna {}",
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ));
+                    }
+
+                    // keep the semantic stack balanced with an untyped LDN
+                    semantic_stack.push_value("LDN", operand2, String::new());
+                }
+            }
+            32 | 47 => {
+                backend.loop_end();
+            }
+            33 | 48 => {
+                // take EXP_R.operand from the semantic stack: the condition
+                // code is inside the loop, so it runs before each repetition
+                let (condition, _) = semantic_stack.pop_value();
+                backend.loop_exit_unless(condition);
+            }
+            40 => {
+                backend.else_begin();
+            }
+            45 => {
+                semantic_stack.append_to_list();
+            }
+            49 | 107 => {
+                // the loop is opened before the condition code is generated
+                backend.loop_begin();
+            }
+            55 => {
+                backend.for_end();
+            }
+            56 | 57 | 123 => {
+                // take the OPRD.operand and OPRD.tk_type of the step (when present),
+                // of the final value and of the initial value from the semantic stack
+                let mut step = (Operand::Int(1), String::from("inteiro"));
+                if r != 57 {
+                    step = semantic_stack.pop_value();
+                }
+                if r == 123 {
+                    // take the opm.lexeme of the sign of the step from the semantic stack
+                    let (_, sign, _) = semantic_stack.top();
+                    let sign = sign.clone();
+                    semantic_stack.pop(1);
+
+                    match sign.as_str() {
+                        "+" => (),
+                        "-" => {
+                            step.0 = match step.0 {
+                                Operand::Int(n) => Operand::Int(n.wrapping_neg()),
+                                // the type of the step is checked below
+                                operand if String::from("inteiro").ne(&step.1) => operand,
+                                operand => {
+                                    let temp_var = backend.new_temp(Type::Inteiro);
+                                    backend.binary_op(temp_var.clone(), Operand::Int(0), Operator::Sub, operand);
+                                    temp_var
+                                }
+                            }
+                        }
+                        _ => self.semantic_error_msgs.push(format!(
+                            "[ESe27] Erro: O passo do 'para' só pode ter o sinal '+' ou '-' na linha {}, coluna {}",
+                            scanner.get_row(),
+                            scanner.get_col()
+                        )),
+                    }
+                }
+                let end = semantic_stack.pop_value();
+                let start = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
+                let (_, lexeme, _) = semantic_stack.top();
+                let lexeme = lexeme.clone();
+                semantic_stack.pop(1);
+                let id = scanner.symbol_table.get(lexeme.clone()).unwrap();
+
+                match &id.tk_type {
+                    None => self.semantic_error_msgs.push(format!(
+                        "[ESe1] Erro: Variável '{}' não declarada na próximo à linha {}, coluna {}",
+                        lexeme,
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    Some(tk_type) if String::from("inteiro").ne(tk_type) => {
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe6] Erro: A variável de controle '{}' do 'para' deve ser do tipo inteiro na linha {}, coluna {}",
+                            lexeme,
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ))
+                    }
+                    _ if id.constant.is_some() => self.constant_changed(scanner, &lexeme),
+                    _ => (),
+                }
+
+                // an empty tk_type means the error was already reported by OPRD
+                if [&start, &end, &step]
+                    .iter()
+                    .any(|(_, tk_type)| !tk_type.is_empty() && String::from("inteiro").ne(tk_type))
+                {
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe7] Erro: Os valores inicial e final e o passo do 'para' devem ser do tipo inteiro na linha {}, coluna {}",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                }
+
+                // with a step of 0 the loop would never end
+                if step.0 == Operand::Int(0) {
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe26] Erro: O passo do 'para' não pode ser 0 na linha {}, coluna {}",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                }
+
+                backend.for_begin(Operand::Var(lexeme), start.0, end.0, step.0);
+            }
+            125 | 126 => {
+                // take LDE.operand (or LDN.operand) and its tk_type from the
+                // semantic stack once
+                let (operand, tk_type) = semantic_stack.pop_value();
+                let item = if r == 125 { "LD" } else { "LDE" };
+                semantic_stack.push_value(item, operand, tk_type);
+            }
+            127 => {
+                // take LDN.operand and LDN.tk_type from the semantic stack
+                let (operand, tk_type) = semantic_stack.pop_value();
+
+                if tk_type.is_empty() {
+                    // the error was already reported
+                    semantic_stack.push_value("LDN", operand, tk_type);
+                } else if String::from("logico").eq(&tk_type) {
+                    // a logico is 1 or 0, so 'nao x' is x = falso
+                    let temp_var = backend.new_temp(Type::Logico);
+                    backend.binary_op(temp_var.clone(), operand, Operator::Eq, Operand::Int(0));
+                    semantic_stack.push_value("LDN", temp_var, tk_type);
+                } else {
+                    // keep the semantic stack balanced with an untyped LDN
+                    semantic_stack.push_value("LDN", operand, String::new());
+                    self.logical_operand_error(scanner);
+                }
+            }
+            63 | 64 => {
+                backend.subprogram_end();
+                scanner.symbol_table.pop_scope();
+                self.subprogram = None;
+            }
+            65 | 66 => {
+                // take the PAR.lexeme and PAR.tk_type of each LPAR item (when
+                // present) from the semantic stack
+                let params = if r == 65 {
+                    semantic_stack.pop_list()
+                } else {
+                    Vec::new()
+                };
+
+                // take NOMEF.lexeme and NOMEF.tk_type from the semantic stack
+                let (_, name, return_type) = semantic_stack.top();
+                let name = name.clone();
+                let return_type = return_type.clone();
                 semantic_stack.pop(1);
 
-                // take EXP_R.lexeme from the semantic stack
+                // the parameters are known only now, but the subprogram was
+                // already registered by NOMEF so it may call itself
+                let param_types = params.iter().map(|(_, tk_type)| tk_type.clone()).collect();
+                scanner.symbol_table.insert_subprogram(
+                    name.clone(),
+                    Subprogram {
+                        return_type: if return_type.is_empty() {
+                            None
+                        } else {
+                            Some(return_type.clone())
+                        },
+                        params: param_types,
+                    },
+                );
+
+                let return_type = Some(return_type.as_str())
+                    .filter(|tk_type| !tk_type.is_empty())
+                    .map(ir_type);
+                let params: Vec<(String, Type)> = params
+                    .into_iter()
+                    .map(|(lexeme, tk_type)| (lexeme, ir_type(&tk_type)))
+                    .collect();
+                backend.subprogram_begin(&name, return_type, &params);
+            }
+            67 | 68 => {
+                // take id.lexeme from the semantic stack
+                let (_, name, _) = semantic_stack.top();
+                let name = name.clone();
+                semantic_stack.pop(1);
+
+                // take TIPO.tk_type from the semantic stack (a procedure has none)
+                let mut return_type = String::new();
+                if r == 67 {
+                    let (_, _, tk_type) = semantic_stack.top();
+                    return_type = tk_type.clone();
+                    semantic_stack.pop(1);
+
+                    if String::from("literal").eq(&return_type) {
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe17] Erro: A função '{}' não pode retornar um literal na linha {}, coluna {}",
+                            name,
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ));
+                    }
+                }
+
+                let declared = scanner.symbol_table.get(name.clone()).unwrap();
+                if declared.tk_type.is_some()
+                    || scanner.symbol_table.get_subprogram(&name).is_some()
+                {
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe15] Erro: Identificador '{}' declarado mais de uma vez na linha {}, coluna {}",
+                        name,
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                }
+
+                scanner.symbol_table.insert_subprogram(
+                    name.clone(),
+                    Subprogram {
+                        return_type: if return_type.is_empty() {
+                            None
+                        } else {
+                            Some(return_type.clone())
+                        },
+                        params: Vec::new(),
+                    },
+                );
+                self.subprogram = Some((
+                    name.clone(),
+                    if return_type.is_empty() {
+                        None
+                    } else {
+                        Some(return_type.clone())
+                    },
+                ));
+
+                // parameters and local variables live in a scope of their own
+                scanner.symbol_table.push_scope();
+
+                semantic_stack.push((String::from("NOMEF"), name, return_type));
+            }
+            69 => {
+                semantic_stack.new_list("LPAR");
+            }
+            70 => {
+                semantic_stack.append_to_list();
+            }
+            79 | 80 => {
+                let (name, return_type) = self.subprogram.clone().unwrap();
+                if (r == 79) != return_type.is_some() {
+                    self.semantic_error_msgs.push(format!(
+                        "[ESe16] Erro: O subprograma '{}' deve terminar com '{}' na linha {}, coluna {}",
+                        name,
+                        if return_type.is_some() {
+                            "fimfuncao"
+                        } else {
+                            "fimprocedimento"
+                        },
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                }
+            }
+            81 => {
+                // take LD.operand and LD.tk_type from the semantic stack
+                let (operand, tk_type) = semantic_stack.pop_value();
+
+                match &self.subprogram {
+                    None => self.semantic_error_msgs.push(format!(
+                        "[ESe13] Erro: 'retorne' fora de um subprograma na linha {}, coluna {}",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    Some((name, None)) => self.semantic_error_msgs.push(format!(
+                        "[ESe14] Erro: O procedimento '{}' não pode retornar um valor na linha {}, coluna {}",
+                        name,
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    // an empty tk_type means the error was already reported by LD
+                    Some((name, Some(return_type)))
+                        if !tk_type.is_empty() && return_type.ne(&tk_type) =>
+                    {
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe14] Erro: A função '{}' deve retornar um valor do tipo {} na linha {}, coluna {}",
+                            name,
+                            return_type,
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ))
+                    }
+                    _ => (),
+                }
+
+                backend.return_value(Some(operand));
+            }
+            82 => {
+                match &self.subprogram {
+                    None => self.semantic_error_msgs.push(format!(
+                        "[ESe13] Erro: 'retorne' fora de um subprograma na linha {}, coluna {}",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    Some((name, Some(return_type))) => self.semantic_error_msgs.push(format!(
+                        "[ESe14] Erro: A função '{}' deve retornar um valor do tipo {} na linha {}, coluna {}",
+                        name,
+                        return_type,
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    _ => (),
+                }
+
+                backend.return_value(None);
+            }
+            88..=91 => {
+                // take the LD.operand and LD.tk_type of each LARGC item (when
+                // present) from the semantic stack
+                let args = if r == 88 || r == 90 {
+                    semantic_stack.pop_value_list()
+                } else {
+                    Vec::new()
+                };
+
+                // take id.lexeme from the semantic stack
+                let (_, name, _) = semantic_stack.top();
+                let name = name.clone();
+                semantic_stack.pop(1);
+
+                let arg_types: Vec<&str> = args.iter().map(|(_, tk_type)| tk_type.as_str()).collect();
+
+                let mut return_type = String::new();
+                match scanner.symbol_table.get_subprogram(&name) {
+                    None => self.semantic_error_msgs.push(format!(
+                        "[ESe9] Erro: Subprograma '{}' não declarado na linha {}, coluna {}",
+                        name,
+                        scanner.get_row(),
+                        scanner.get_col()
+                    )),
+                    Some(subprogram) => {
+                        if subprogram.params.len() != arg_types.len() {
+                            self.semantic_error_msgs.push(format!(
+                                "[ESe10] Erro: O subprograma '{}' espera {} argumento(s), mas recebeu {} na linha {}, coluna {}",
+                                name,
+                                subprogram.params.len(),
+                                arg_types.len(),
+                                scanner.get_row(),
+                                scanner.get_col()
+                            ));
+                        } else {
+                            // an empty tk_type means the error was already reported by LD
+                            for (i, (param, arg)) in
+                                subprogram.params.iter().zip(arg_types.iter()).enumerate()
+                            {
+                                if !arg.is_empty() && param.ne(arg) {
+                                    self.semantic_error_msgs.push(format!(
+                                        "[ESe11] Erro: O argumento {} de '{}' deve ser do tipo {} na linha {}, coluna {}",
+                                        i + 1,
+                                        name,
+                                        param,
+                                        scanner.get_row(),
+                                        scanner.get_col()
+                                    ));
+                                }
+                            }
+                        }
+
+                        match subprogram.return_type {
+                            None if r == 90 || r == 91 => {
+                                self.semantic_error_msgs.push(format!(
+                                    "[ESe12] Erro: O procedimento '{}' não retorna um valor e não pode ser usado em uma expressão na linha {}, coluna {}",
+                                    name,
+                                    scanner.get_row(),
+                                    scanner.get_col()
+                                ))
+                            }
+                            Some(tk_type) => return_type = tk_type,
+                            _ => (),
+                        }
+                    }
+                }
+
+                let args: Vec<Operand> = args.into_iter().map(|(operand, _)| operand).collect();
+                if r == 88 || r == 89 {
+                    backend.call(None, &name, args);
+                } else if return_type.is_empty() {
+                    // keep the semantic stack balanced with an untyped OPRD
+                    semantic_stack.push_value("OPRD", Operand::Var(name), String::new());
+                } else {
+                    let temp_var = backend.new_temp(ir_type(&return_type));
+                    backend.call(Some(temp_var.clone()), &name, args);
+                    semantic_stack.push_value("OPRD", temp_var, return_type);
+                }
+            }
+            92 => {
+                semantic_stack.new_list("LARGC");
+            }
+            93 => {
+                semantic_stack.append_to_list();
+            }
+            96 => {
+                // take LD.operand and LD.tk_type (the index) from the semantic stack
+                let index = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
                 let (_, lexeme, _) = semantic_stack.top();
                 let lexeme = lexeme.clone();
                 semantic_stack.pop(1);
 
-                obj_file.print(format!("    if ({}) {{\n", lexeme));
+                if let Some((access, tk_type)) = self.index_access(scanner, backend, lexeme, index) {
+                    backend.read(access, ir_type(&tk_type));
+                }
             }
-            26 => {
-                // take OPRD.lexeme and OPRD.tk_type from the semantic stack twice
-                let (_, lexeme, tk_type) = semantic_stack.top();
-                let lexeme1 = lexeme.clone();
-                let tk_type1 = tk_type.clone();
+            97 => {
+                // take LD.operand and LD.tk_type (the value) from the semantic stack
+                let (ld_operand, ld_tk_type) = semantic_stack.pop_value();
+
+                // take LD.operand and LD.tk_type (the index) from the semantic stack
+                let index = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
+                let (_, lexeme, _) = semantic_stack.top();
+                let lexeme = lexeme.clone();
                 semantic_stack.pop(1);
 
-                let (_, lexeme, tk_type) = semantic_stack.top();
-                let lexeme2 = lexeme.clone();
-                let tk_type2 = tk_type.clone();
-                semantic_stack.pop(1);
+                if let Some((access, tk_type)) = self.index_access(scanner, backend, lexeme, index) {
+                    if ld_tk_type.eq(&tk_type) {
+                        backend.assign(access, ld_operand, ir_type(&tk_type));
+                    } else if !ld_tk_type.is_empty() {
+                        // an empty tk_type means the error was already reported by LD
+                        self.semantic_error_msgs.push(format!(
+                            "[ESe3] Erro: Tipos diferentes para atribuição na linha {}, coluna {}",
+                            scanner.get_row(),
+                            scanner.get_col()
+                        ));
+                    }
+                }
+            }
+            98 => {
+                // take LD.operand and LD.tk_type (the index) from the semantic stack
+                let index = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
+                let (_, lexeme, _) = semantic_stack.top();
+                let lexeme = lexeme.clone();
+                semantic_stack.pop(1);
+
+                match self.index_access(scanner, backend, lexeme.clone(), index) {
+                    Some((access, tk_type)) => semantic_stack.push_value("OPRD", access, tk_type),
+                    // keep the semantic stack balanced with an untyped OPRD
+                    None => semantic_stack.push_value("OPRD", Operand::Var(lexeme), String::new()),
+                }
+            }
+            99 => {
+                let token = scanner.symbol_table.get(String::from("logico")).unwrap();
+                semantic_stack.push((
+                    String::from("TIPO"),
+                    String::from(""),
+                    token.tk_type.unwrap(),
+                ));
+            }
+            100 => {
+                semantic_stack.push_value(
+                    "OPRD",
+                    logical_value(token),
+                    token.tk_type.as_ref().unwrap().clone(),
+                );
+            }
+            102 | 124 => {
+                // take the operands of the 'e' (LDE.operand and LDN.operand) or of
+                // the 'ou' (LD.operand and LDE.operand), with their tk_types, from
+                // the semantic stack; both are always evaluated
+                let (operand1, tk_type1) = semantic_stack.pop_value();
+                let (operand2, tk_type2) = semantic_stack.pop_value();
+                let (item, operator) = if r == 102 {
+                    ("LDE", Operator::And)
+                } else {
+                    ("LD", Operator::Or)
+                };
+
+                if tk_type1.is_empty() || tk_type2.is_empty() {
+                    // the error was already reported
+                    semantic_stack.push_value(item, operand2, String::new());
+                } else if String::from("logico").eq(&tk_type1) && String::from("logico").eq(&tk_type2) {
+                    let temp_var = backend.new_temp(Type::Logico);
+                    backend.binary_op(temp_var.clone(), operand2, operator, operand1);
+                    semantic_stack.push_value(item, temp_var, tk_type1);
+                } else {
+                    // keep the semantic stack balanced with an untyped LDE or LD
+                    semantic_stack.push_value(item, operand2, String::new());
+                    self.logical_operand_error(scanner);
+                }
+            }
+            103 => {
+                // take VCTE.operand and VCTE.tk_type from the semantic stack
+                let (value, tk_type) = semantic_stack.pop_value();
+
+                // take id.lexeme from the semantic stack
+                let (_, lexeme, _) = semantic_stack.top();
+                let lexeme = lexeme.clone();
+                semantic_stack.pop(1);
+
+                // the type of a constant comes from its value, and nothing is
+                // printed in the obj file: the value replaces the name wherever
+                // the constant is used
+                self.declare_variable(scanner, lexeme, tk_type, Some(value));
+            }
+            104 => {
+                let operand = self.number(scanner, token);
+                semantic_stack.push_value("VCTE", operand, token.tk_type.as_ref().unwrap().clone());
+            }
+            105 => {
+                semantic_stack.push_value(
+                    "VCTE",
+                    literal(token.lexeme.as_ref().unwrap()),
+                    token.tk_type.as_ref().unwrap().clone(),
+                );
+            }
+            106 => {
+                semantic_stack.push_value(
+                    "VCTE",
+                    logical_value(token),
+                    token.tk_type.as_ref().unwrap().clone(),
+                );
+            }
+            _ => (),
+        }
+    }
+
+    fn array_without_index(&mut self, scanner: &Scanner, lexeme: &str) {
+        self.semantic_error_msgs.push(format!(
+            "[ESe18] Erro: O vetor '{}' deve ser acompanhado de um índice na linha {}, coluna {}",
+            lexeme,
+            scanner.get_row(),
+            scanner.get_col()
+        ));
+    }
+
+    // declare a variable (or a named constant, when its value is given)
+    // in the innermost scope
+    fn declare_variable(
+        &mut self,
+        scanner: &mut Scanner,
+        lexeme: String,
+        tk_type: String,
+        constant: Option<Operand>,
+    ) {
+        if scanner.symbol_table.is_declared_in_scope(&lexeme)
+            || scanner.symbol_table.get_subprogram(&lexeme).is_some()
+        {
+            self.semantic_error_msgs.push(format!(
+                "[ESe15] Erro: Identificador '{}' declarado mais de uma vez na linha {}, coluna {}",
+                lexeme,
+                scanner.get_row(),
+                scanner.get_col()
+            ));
+        }
+
+        let mut id = Token::new(String::from("id"), Some(lexeme.clone()), Some(tk_type));
+        id.constant = constant;
+        scanner.symbol_table.declare(lexeme, id);
+    }
 
-                if String::from("literal").ne(&tk_type1) && String::from("literal").ne(&tk_type2) {
-                    let mut _temp_var = 0;
-                    unsafe {
-                        _temp_var = TEMP_VAR;
-                        TEMP_VAR += 1;
-                    }
-                    semantic_stack.push((
-                        String::from("EXP_R"),
-                        format!("T{}", _temp_var),
-                        String::new(),
-                    ));
+    // The operand of a num Token; when its value doesn't fit in its
+    // type the error is reported and 0 is used instead. A lit Token
+    // also reduces by rule 22 and gives a literal.
+    fn number(&mut self, scanner: &Scanner, token: &Token) -> Operand {
+        let lexeme = token.lexeme.as_ref().unwrap();
+        let tk_type = token.tk_type.as_ref().unwrap();
 
-                    // insert Tx_expr in the stack
-                    semantic_stack.push((
-                        String::from("Tx_expr"),
-                        format!("T{} = {} {} {}", _temp_var, lexeme2, last_seen_operator, lexeme1),
-                        String::new(),
-                    ));
+        if !String::from("num").eq(&token.class) {
+            return literal(lexeme);
+        }
 
-                    obj_file.add_temp_var( TempVarType::Int);
-                    obj_file.print(format!(
-                        "    T{} = {} {} {};\n",
-                        _temp_var, lexeme2, last_seen_operator, lexeme1
-                    ));
+        match number_operand(lexeme, tk_type) {
+            Some(number) => number,
+            None => {
+                let note = if String::from("inteiro").eq(tk_type) {
+                    format!("um inteiro deve estar entre {} e {}", i32::MIN, i32::MAX)
                 } else {
-                    self.semantic_error_msgs.push(format!(
-                        "[ESe5] Erro: Operandos com tipos incompatíveis para comparação na linha {}, coluna {}",
-                        scanner.get_row(),
-                        scanner.get_col()
-                    ));
-                }
+                    format!("um real deve ser no máximo {:e}", f64::MAX)
+                };
+                self.semantic_error_msgs.push(format!(
+                    "[ESe25] Erro: O número '{}' está fora do intervalo do tipo {} na linha {}, coluna {}\n    NOTA: {}",
+                    lexeme,
+                    tk_type,
+                    scanner.get_row(),
+                    scanner.get_col(),
+                    note
+                ));
+                Operand::Int(0)
             }
-            32 => {
-                obj_file.print(format!("    }}\n"));
+        }
+    }
+
+    fn constant_changed(&mut self, scanner: &Scanner, lexeme: &str) {
+        self.semantic_error_msgs.push(format!(
+            "[ESe24] Erro: A constante '{}' não pode ser alterada na linha {}, coluna {}",
+            lexeme,
+            scanner.get_row(),
+            scanner.get_col()
+        ));
+    }
+
+    fn logical_operand_error(&mut self, scanner: &Scanner) {
+        self.semantic_error_msgs.push(format!(
+            "[ESe28] Erro: Os operandos de 'e', 'ou' e 'nao' devem ser do tipo logico na linha {}, coluna {}",
+            scanner.get_row(),
+            scanner.get_col()
+        ));
+    }
+
+    // check an indexed access v[i] and return its operand and the type
+    // of the array elements, or None if the access is not valid
+    fn index_access(
+        &mut self,
+        scanner: &Scanner,
+        backend: &mut dyn Backend,
+        lexeme: String,
+        index: (Operand, String),
+    ) -> Option<(Operand, String)> {
+        let id = scanner.symbol_table.get(lexeme.clone()).unwrap();
+        let (tk_type, size) = match id.tk_type.as_deref().map(array_type) {
+            None => {
+                self.semantic_error_msgs.push(format!(
+                    "[ESe2] Erro: Variável '{}' não declarada na linha {}, coluna {}",
+                    lexeme,
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                return None;
             }
-            33 => {
-                // take Tx_expr from the semantic stack
-                let (_, ex_expr, _) = semantic_stack.top();
-                let ex_expr = ex_expr.clone();
-                semantic_stack.pop(1);
+            Some(None) => {
+                self.semantic_error_msgs.push(format!(
+                    "[ESe19] Erro: A variável '{}' não é um vetor e não pode ser indexada na linha {}, coluna {}",
+                    lexeme,
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                return None;
+            }
+            Some(Some(array)) => array,
+        };
 
-                // take EXP_R.lexeme from the semantic stack
-                let (_, lexeme, _) = semantic_stack.top();
-                let lexeme = lexeme.clone();
-                semantic_stack.pop(1);
+        // an empty tk_type means the error was already reported by LD
+        let (index, index_tk_type) = index;
+        if index_tk_type.is_empty() {
+            return None;
+        }
+        if String::from("inteiro").ne(&index_tk_type) {
+            self.semantic_error_msgs.push(format!(
+                "[ESe20] Erro: O índice do vetor '{}' deve ser do tipo inteiro na linha {}, coluna {}",
+                lexeme,
+                scanner.get_row(),
+                scanner.get_col()
+            ));
+            return None;
+        }
 
-                obj_file.print(format!("    for(; {}; {}) {{\n", lexeme, ex_expr));
+        // a constant index is checked now, any other one only at runtime
+        match index {
+            Operand::Int(i) if i < 0 || i as usize >= size => {
+                self.semantic_error_msgs.push(format!(
+                    "[ESe21] Erro: O índice {} está fora dos limites do vetor '{}' (de 0 a {}) na linha {}, coluna {}",
+                    i,
+                    lexeme,
+                    size - 1,
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                None
             }
-            _ => (),
+            Operand::Int(_) => Some((backend.index(&lexeme, index), tk_type)),
+            _ if self.bounds_check => Some((
+                backend.checked_index(&lexeme, index, size, scanner.get_row()),
+                tk_type,
+            )),
+            _ => Some((backend.index(&lexeme, index), tk_type)),
         }
     }
 
@@ -552,7 +1304,7 @@ impl Parser {
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return true;
+                true
             }
             // missing ';'
             2 => {
@@ -568,7 +1320,7 @@ impl Parser {
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return true;
+                true
             }
             // two or more ';' in sequence
             3 => {
@@ -580,7 +1332,7 @@ impl Parser {
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return true;
+                true
             }
             // invalid token after a ';'
             4 => {
@@ -593,7 +1345,7 @@ impl Parser {
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
             // '(' expected after a 'se' keyword
             5 => {
@@ -612,7 +1364,7 @@ impl Parser {
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
             // '(' expected after a 'se' keyword, but and 'id' or a 'num' was found
             6 => {
@@ -627,25 +1379,25 @@ impl Parser {
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return true;
+                true
             }
-            // opr, opm, ')' or ';' expected after a 'id'
+            // opr, opm, ',', ')' or ';' expected after a 'id'
             7 => {
                 self.syntatic_error_msgs.push(format!(
-                    "[ES7] Erro sintático na linha {}, coluna {}: após um identificador deve vir um operador relacional, um operador aritimético, um ')' ou um ';'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    "[ES7] Erro sintático na linha {}, coluna {}: após um identificador deve vir um operador relacional, um operador aritimético, uma ',', um ')' ou um ';'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return false;
+                false
             }
-            // opr, opm, ')' or ';' expected after a 'num'
+            // opr, opm, ',', ')' or ';' expected after a 'num'
             8 => {
                 self.syntatic_error_msgs.push(format!(
-                    "[ES8] Erro sintático na linha {}, coluna {}: após um número deve vir um operador relacional, um operador aritimético, um ')' ou um ';'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    "[ES8] Erro sintático na linha {}, coluna {}: após um número deve vir um operador relacional, um operador aritimético, uma ',', um ')' ou um ';'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return false;
+                false
             }
             // 'id'/'num' not found after a 'se ('
             9 => {
@@ -659,7 +1411,7 @@ impl Parser {
                         scanner.get_row(),
                         scanner.get_col()
                     ));
-                    return false;
+                    false
                 } else {
                     self.syntatic_error_msgs.push(format!(
                         "[ES9.2] Erro sintático na linha {}, coluna {}: esperado um 'id' ou um 'num' após um 'se ('\n    NOTA: o token '{}' foi removido",
@@ -667,39 +1419,21 @@ impl Parser {
                         scanner.get_col(),
                         token.lexeme.unwrap()
                     ));
-                    return true;
+                    true
                 }
             }
-            // 'opr' not found after the 1st argument in a relacional expression
-            10 => {
-                self.syntatic_error_msgs.push(format!(
-                    "[ES10] Erro sintático na linha {}, coluna {}: não encontrado '<', '>', '>=', '<=', '=' ou '<>' após o primeiro argumento de uma expressão relacional\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
-                    scanner.get_row(),
-                    scanner.get_col()
-                ));
-                return false;
-            }
-            // 'num' nor 'id' found after a 'opr'
-            11 => {
-                self.syntatic_error_msgs.push(format!(
-                    "[ES11] Erro sintático na linha {}, coluna {}: esperado um 'num' ou um 'id' após um operador relacional\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
-                    scanner.get_row(),
-                    scanner.get_col()
-                ));
-                return false;
-            }
-            // opr, opm or ';' found after a relacional expression
+            // some token but ')' or 'ou' found after a condition
             12 => {
                 // remove the wrong token
                 let token = self.token_buffer.pop();
 
                 self.syntatic_error_msgs.push(format!(
-                    "[ES12] Erro sintático na linha {}, coluna {}: após uma expressão relacional, é esperado um ')'\n    NOTA: o token '{}' foi removido",
+                    "[ES12] Erro sintático na linha {}, coluna {}: após a condição, é esperado um ')'\n    NOTA: o token '{}' foi removido",
                     scanner.get_row(),
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
             // some token but not 'entao' ater a 'se ( EXP_R )'
             13 => {
@@ -712,20 +1446,20 @@ impl Parser {
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
-            // 'leia', 'escreva', 'id', 'se' or 'fimse' a 'entao'
+            // 'leia', 'escreva', 'id', 'se', 'senao' or 'fimse' a 'entao'
             14 => {
                 // remove the wrong token
                 let token = self.token_buffer.pop();
 
                 self.syntatic_error_msgs.push(format!(
-                    "[ES14] Erro sintático na linha {}, coluna {}: esperado 'leia', 'escreva', 'id', 'se' ou 'fimse' após a palavra reservada 'entao'\n    NOTA: o token '{}' foi removido",
+                    "[ES14] Erro sintático na linha {}, coluna {}: esperado 'leia', 'escreva', 'id', 'se', 'senao' ou 'fimse' após a palavra reservada 'entao'\n    NOTA: o token '{}' foi removido",
                     scanner.get_row(),
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
             // '(' expected after a 'repita' keyword
             15 => {
@@ -744,7 +1478,7 @@ impl Parser {
                     scanner.get_col(),
                     token.unwrap().lexeme.unwrap()
                 ));
-                return true;
+                true
             }
             // '(' expected after a 'repita' keyword, but and 'id' or a 'num' was found
             16 => {
@@ -759,7 +1493,7 @@ impl Parser {
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return true;
+                true
             }
             // 'id'/'num' not found after a 'repita ('
             17 => {
@@ -773,7 +1507,7 @@ impl Parser {
                         scanner.get_row(),
                         scanner.get_col()
                     ));
-                    return false;
+                    false
                 } else {
                     self.syntatic_error_msgs.push(format!(
                         "[ES17.2] Erro sintático na linha {}, coluna {}: esperado um 'id' ou um 'num' após um 'repita ('\n    NOTA: o token '{}' foi removido",
@@ -781,16 +1515,291 @@ impl Parser {
                         scanner.get_col(),
                         token.lexeme.unwrap()
                     ));
-                    return true;
+                    true
+                }
+            }
+            // 'senao' outside of a 'se ... entao' block
+            18 => {
+                // remove the wrong token
+                let token = self.token_buffer.pop();
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES18] Erro sintático na linha {}, coluna {}: 'senao' sem uma estrutura condicional 'se ... entao' correspondente\n    NOTA: o token '{}' foi removido",
+                    scanner.get_row(),
+                    scanner.get_col(),
+                    token.unwrap().lexeme.unwrap()
+                ));
+                true
+            }
+            // a second 'senao' in the same conditional structure
+            19 => {
+                // remove the wrong token
+                let token = self.token_buffer.pop();
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES19] Erro sintático na linha {}, coluna {}: uma estrutura condicional deve ter no máximo um 'senao'\n    NOTA: o token '{}' foi removido",
+                    scanner.get_row(),
+                    scanner.get_col(),
+                    token.unwrap().lexeme.unwrap()
+                ));
+                true
+            }
+            // no argument after a ',' in a 'escreva'
+            20 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES20] Erro sintático na linha {}, coluna {}: esperado um literal, um número ou um identificador após uma ',' no 'escreva'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // '(' expected after a 'enquanto' keyword
+            21 => {
+                // remove the wrong token
+                let token = self.token_buffer.pop();
+
+                self.token_buffer.push(Token::new(
+                    String::from("ab_p"),
+                    Some(String::from("(")),
+                    None,
+                ));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES21] Erro sintático na linha {}, coluna {}: esperado um '(' após a palavra reservada 'enquanto'\n    NOTA: o token '{}' foi removido",
+                    scanner.get_row(),
+                    scanner.get_col(),
+                    token.unwrap().lexeme.unwrap()
+                ));
+                true
+            }
+            // '(' expected after a 'enquanto' keyword, but and 'id' or a 'num' was found
+            22 => {
+                self.token_buffer.push(Token::new(
+                    String::from("ab_p"),
+                    Some(String::from("(")),
+                    None,
+                ));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES22] Erro sintático na linha {}, coluna {}: esperado um '(' após a palavra reservada 'enquanto'",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                true
+            }
+            // 'id'/'num' not found after a 'enquanto ('
+            23 => {
+                // remove the wrong token
+                let token = self.token_buffer.pop().unwrap();
+
+                if token.class.eq("fc_p") {
+                    // '()' cannot be recovered
+                    self.syntatic_error_msgs.push(format!(
+                        "[ES23.1] Erro sintático na linha {}, coluna {}: encontrado um () após a palavra reservada 'enquanto'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                        scanner.get_row(),
+                        scanner.get_col()
+                    ));
+                    false
+                } else {
+                    self.syntatic_error_msgs.push(format!(
+                        "[ES23.2] Erro sintático na linha {}, coluna {}: esperado um 'id' ou um 'num' após um 'enquanto ('\n    NOTA: o token '{}' foi removido",
+                        scanner.get_row(),
+                        scanner.get_col(),
+                        token.lexeme.unwrap()
+                    ));
+                    true
                 }
             }
+            // no control variable after a 'para' keyword
+            24 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES24] Erro sintático na linha {}, coluna {}: esperado um identificador após a palavra reservada 'para'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // 'de' expected after the control variable of a 'para'
+            25 => {
+                // since a 'de' is missing, put it into the input
+                self.token_buffer.push(Token::new_from_lexeme("de"));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES25] Erro sintático na linha {}, coluna {}: esperado a palavra reservada 'de' após a variável de controle do 'para'",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                true
+            }
+            // 'id'/'num' not found after a 'de', 'ate' or 'passo'
+            26 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES26] Erro sintático na linha {}, coluna {}: esperado um 'id' ou um 'num' após 'de', 'ate' ou 'passo'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // 'ate' expected after the initial value of a 'para'
+            27 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES27] Erro sintático na linha {}, coluna {}: esperado a palavra reservada 'ate' após o valor inicial do 'para'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // type not found after a 'funcao'
+            28 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES28] Erro sintático na linha {}, coluna {}: esperado o tipo de retorno após a palavra reservada 'funcao'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // name not found after 'funcao TIPO' or 'procedimento'
+            29 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES29] Erro sintático na linha {}, coluna {}: esperado o nome do subprograma\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // '(' not found after the name of a subprogram
+            30 => {
+                self.token_buffer.push(Token::new(
+                    String::from("ab_p"),
+                    Some(String::from("(")),
+                    None,
+                ));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES30] Erro sintático na linha {}, coluna {}: esperado um '(' após o nome do subprograma",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                true
+            }
+            // neither a parameter nor ')' found after 'funcao TIPO id (' or a ','
+            31 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES31] Erro sintático na linha {}, coluna {}: esperado um parâmetro ou ')'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // name not found after the type of a parameter
+            32 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES32] Erro sintático na linha {}, coluna {}: esperado o nome do parâmetro\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // neither ',' nor ')' found after a parameter
+            33 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES33] Erro sintático na linha {}, coluna {}: esperado ',' ou ')' após o parâmetro\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // argument not found after '(' or ',' in a call
+            34 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES34] Erro sintático na linha {}, coluna {}: esperado um argumento\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // neither ',' nor ')' found after an argument in a call
+            35 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES35] Erro sintático na linha {}, coluna {}: esperado ',' ou ')' após o argumento\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // no index after a '['
+            36 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES36] Erro sintático na linha {}, coluna {}: esperado um índice após o '['\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // no size after a '[' in a declaration
+            37 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES37] Erro sintático na linha {}, coluna {}: esperado o tamanho do vetor após o '['\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // ']' not found after an index or an array size
+            38 => {
+                self.token_buffer.push(Token::new(
+                    String::from("fc_c"),
+                    Some(String::from("]")),
+                    None,
+                ));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES38] Erro sintático na linha {}, coluna {}: esperado um ']'",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                true
+            }
+            // no name after a 'constante'
+            39 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES39] Erro sintático na linha {}, coluna {}: esperado o nome da constante após a palavra reservada 'constante'\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
+            // '<-' not found after the name of a constant
+            40 => {
+                self.token_buffer.push(Token::new(
+                    String::from("rcb"),
+                    Some(String::from("<-")),
+                    None,
+                ));
+
+                self.syntatic_error_msgs.push(format!(
+                    "[ES40] Erro sintático na linha {}, coluna {}: esperado um '<-' após o nome da constante",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                true
+            }
+            // no value after 'constante id <-'
+            41 => {
+                self.syntatic_error_msgs.push(format!(
+                    "[ES41] Erro sintático na linha {}, coluna {}: esperado um número, um literal, 'verdadeiro' ou 'falso' como valor da constante\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
+                    scanner.get_row(),
+                    scanner.get_col()
+                ));
+                false
+            }
             _ => {
                 self.syntatic_error_msgs.push(format!(
                     "[ES0] Erro sintático na linha {}, coluna {}\n    NOTA: não é possível recuperar deste erro e portanto a análise foi interrompida",
                     scanner.get_row(),
                     scanner.get_col()
                 ));
-                return false;
+                false
             }
         }
     }
@@ -829,3 +1838,46 @@ impl Parser {
         n as u8
     }
 }
+
+// the element type and the size of an array type such as inteiro[10]
+fn array_type(tk_type: &str) -> Option<(String, usize)> {
+    let (tk_type, size) = tk_type.strip_suffix(']')?.split_once('[')?;
+    Some((String::from(tk_type), size.parse().ok()?))
+}
+
+// The operand of a num: 010 is the inteiro 10, not an octal one as
+// in C. None means the value doesn't fit in a 32-bit integer or in a
+// double.
+fn number_operand(lexeme: &str, tk_type: &str) -> Option<Operand> {
+    if String::from("inteiro").eq(tk_type) {
+        lexeme.parse::<i32>().ok().map(Operand::Int)
+    } else {
+        lexeme
+            .parse::<f64>()
+            .ok()
+            .filter(|x| x.is_finite())
+            .map(Operand::Real)
+    }
+}
+
+// the operand of a lit, whose lexeme keeps its quotes
+fn literal(lexeme: &str) -> Operand {
+    let text = lexeme.strip_prefix('"').unwrap_or(lexeme);
+    Operand::Lit(String::from(text.strip_suffix('"').unwrap_or(text)))
+}
+
+// verdadeiro and falso are the numbers 1 and 0 in the generated code
+fn logical_value(token: &Token) -> Operand {
+    Operand::Int(String::from("verdadeiro").eq(token.lexeme.as_ref().unwrap()) as i32)
+}
+
+// the Type of a MGol type name; an empty or unknown one only comes
+// with an error, when the program is not used
+fn ir_type(tk_type: &str) -> Type {
+    Type::from_name(tk_type).unwrap_or(Type::Inteiro)
+}
+
+// the Operator of an opm or opr lexeme
+fn operator_of(lexeme: &str) -> Operator {
+    Operator::from_lexeme(lexeme).unwrap_or(Operator::Add)
+}
@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use super::encoding::*;
 use super::lexical_automaton::*;
 use super::symbol_table::*;
 use super::token::*;
@@ -14,6 +15,8 @@ pub struct Scanner {
     file: BufReader<File>,
     line: Vec<char>,
     cursor: (usize, usize), // (row, col)
+    tab_width: usize,
+    encoding: Encoding,
     pub symbol_table: SymbolTable,
     error_msgs: Vec<String>,
 }
@@ -31,6 +34,8 @@ impl Scanner {
             file,
             line,
             cursor,
+            tab_width: 4,
+            encoding: Encoding::Utf8,
             symbol_table,
             error_msgs,
         }
@@ -48,8 +53,14 @@ impl Scanner {
         let mut lexeme = String::new();
         let mut automaton = Automaton::new();
 
+        // where the token (or comment) being read starts
+        let mut start = (0, 0);
+
         // read the code until the EOF
         while let Some(c) = self.read_char() {
+            if let AutomatonState::Initial = automaton.state {
+                start = (self.get_row(), self.get_col());
+            }
             automaton.advance(c);
 
             match automaton.action {
@@ -60,15 +71,45 @@ impl Scanner {
                 Action::None => (),
             }
 
+            // A literal not closed in its line is reported where it starts and
+            // taken as if it were closed there, so the next line is read as usual
+            // and the parser doesn't stop at a missing literal
+            if let AutomatonState::Error(7) = automaton.state {
+                self.error_msgs.push(format!(
+                    "[EL9] Erro léxico na linha {}, coluna {}: literal aberto aqui não foi fechado com '\"' antes do fim da linha",
+                    start.0, start.1
+                ));
+                lexeme.push('"');
+                return self.build_token(lexeme, AutomatonState::Accept(4));
+            }
+
             if automaton.done {
                 return self.build_token(lexeme, automaton.state);
             }
         }
 
         // Perhaps the last code piece has not been parsed. Do it now!
-        if lexeme.len() > 0 {
+        if !lexeme.is_empty() {
             match automaton.state {
                 AutomatonState::Accept(_) => return self.build_token(lexeme, automaton.state),
+                // a '//' comment may end at the EOF
+                AutomatonState::NonAccept('i') => (),
+                // an unfinished comment or literal is reported where it starts,
+                // since the EOF may be far away from it
+                AutomatonState::NonAccept('e') => {
+                    self.error_msgs.push(format!(
+                        "[EL5] Erro léxico na linha {}, coluna {}: comentário aberto aqui não foi fechado com '}}' até o fim do arquivo",
+                        start.0, start.1
+                    ));
+                    return self.build_token(lexeme, AutomatonState::Error(5));
+                }
+                AutomatonState::NonAccept('d' | 'f' | 'g' | 'h') => {
+                    self.error_msgs.push(format!(
+                        "[EL5] Erro léxico na linha {}, coluna {}: literal aberto aqui não foi fechado com '\"' até o fim do arquivo",
+                        start.0, start.1
+                    ));
+                    return self.build_token(lexeme, AutomatonState::Error(5));
+                }
                 AutomatonState::NonAccept(_) => {
                     automaton.state = AutomatonState::Error(5);
                     self.insert_error_msg(' ', &automaton.state);
@@ -96,8 +137,33 @@ impl Scanner {
         self.cursor.0
     }
 
+    // the column of the last read character, as shown by an editor:
+    // a tab goes to the next multiple of the tab width
     pub fn get_col(&self) -> usize {
-        self.cursor.1
+        if self.cursor.1 == 0 {
+            return 0;
+        }
+
+        let mut col = 0;
+        for c in &self.line[..self.cursor.1 - 1] {
+            if *c == '\t' {
+                col = (col / self.tab_width + 1) * self.tab_width;
+            } else {
+                col += 1;
+            }
+        }
+
+        col + 1
+    }
+
+    // set the encoding the source file was saved with
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    // set how many columns a tab takes in the error messages
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
     }
 
     // return a char by consuming the internal BufReader file
@@ -107,16 +173,40 @@ impl Scanner {
         if self.cursor.1 == self.line.len() {
             self.cursor.0 += 1;
             self.cursor.1 = 0;
-            let mut s = String::new();
-            match self.file.read_line(&mut s) {
+            let mut bytes = Vec::new();
+            match self.file.read_until(b'\n', &mut bytes) {
                 Ok(0) => {
                     // EOF
                     unsafe {
                         EOF_REACHED = true;
                     }
                 }
-                Ok(_) => self.line = s.chars().collect(),
-                Err(_) => (),
+                Ok(_) => {
+                    // The file may start with a UTF-8 BOM, even when the
+                    // chosen encoding is another one. Its bytes are dropped
+                    // before decoding, where they would become "ï»¿".
+                    if self.cursor.0 == 1 && bytes.starts_with(b"\xef\xbb\xbf") {
+                        bytes.drain(..3);
+                    }
+
+                    // a line that is not valid in the chosen encoding is kept
+                    // (with the invalid bytes replaced) and reported
+                    let (mut s, invalid_col) = self.encoding.decode(&bytes);
+                    if let Some(col) = invalid_col {
+                        self.error_msgs.push(format!(
+                            "[EL7] Erro léxico na linha {}, coluna {}: o arquivo não está em UTF-8\n    NOTA: se ele foi salvo em um editor do Windows, use a opção --encoding=cp1252 (ou --encoding=latin1)",
+                            self.cursor.0, col
+                        ));
+                    }
+
+                    // a file saved on Windows ends its lines with "\r\n"
+                    if s.ends_with("\r\n") {
+                        s.truncate(s.len() - 2);
+                        s.push('\n');
+                    }
+                    self.line = s.chars().collect();
+                }
+                Err(_) => panic!("Não foi possível ler a linha {} do código fonte", self.cursor.0),
             }
         }
 
@@ -168,6 +258,14 @@ impl Scanner {
                 "[EL5] Erro léxico. Não encontrado o fechamento do comentário ou literal que termina na linha {}, coluna {}",
                 row, col
             )),
+            AutomatonState::NonAccept('g') => self.error_msgs.push(format!(
+                "[EL8] Erro léxico na linha {}, coluna {}: sequência de escape desconhecida '\\{}' em um literal\n    NOTA: as sequências válidas são \\n, \\t, \\\" e \\\\",
+                row, col, c
+            )),
+            AutomatonState::Error(6) => self.error_msgs.push(format!(
+                "[EL6] Erro léxico na linha {}, coluna {}: {:?} não é permitido em um identificador, que deve ter apenas letras sem acento, dígitos e '_'",
+                row, col, c
+            )),
             _ => (),
         }
     }
@@ -233,10 +331,15 @@ impl Scanner {
             AutomatonState::Accept(12) => class = String::from("opr"),
             AutomatonState::Accept(13) => class = String::from("opr"),
             AutomatonState::Accept(14) => class = String::from("opr"),
-            AutomatonState::Accept(15) => class = String::from("opm"),
+            AutomatonState::Accept(15) | AutomatonState::Accept(22) => {
+                class = String::from("opm")
+            }
             AutomatonState::Accept(16) => class = String::from("ab_p"),
             AutomatonState::Accept(17) => class = String::from("fc_p"),
             AutomatonState::Accept(18) => class = String::from("pt_v"),
+            AutomatonState::Accept(19) => class = String::from("vir"),
+            AutomatonState::Accept(20) => class = String::from("ab_c"),
+            AutomatonState::Accept(21) => class = String::from("fc_c"),
             AutomatonState::Error(_) => {
                 class = String::from("ERROR");
                 lexeme = None;
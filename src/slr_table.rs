@@ -28,6 +28,12 @@ pub struct ActionTable {
     table: HashMap<(u8, String), SlrAction>,
 }
 
+impl Default for ActionTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ActionTable {
     pub fn new() -> ActionTable {
         let terminals = [
@@ -54,6 +60,28 @@ impl ActionTable {
             "inteiro",
             "literal",
             "real",
+            "senao",
+            "vir",
+            "enquanto",
+            "fimenquanto",
+            "para",
+            "de",
+            "ate",
+            "passo",
+            "fimpara",
+            "funcao",
+            "procedimento",
+            "fimfuncao",
+            "fimprocedimento",
+            "retorne",
+            "ab_c",
+            "fc_c",
+            "logico",
+            "vlog",
+            "constante",
+            "e",
+            "ou",
+            "nao",
             "EOF",
         ];
 
@@ -71,16 +99,14 @@ impl ActionTable {
         lines.next();
 
         let mut table = HashMap::new();
-        for line in lines {
-            if let Ok(okline) = line {
-                let actions = okline.split(",").collect::<Vec<&str>>();
-                let state = actions[0].parse::<u8>().unwrap();
-                // HashMap<(u8, String), SlrAction>
-                for i in 0..terminals.len() {
-                    let action = actions[i + 1];
-                    let terminal = terminals[i];
-                    table.insert((state, String::from(terminal)), str_to_action(action));
-                }
+        for line in lines.map_while(Result::ok) {
+            let actions = line.split(',').collect::<Vec<&str>>();
+            let state = actions[0].parse::<u8>().unwrap();
+            // HashMap<(u8, String), SlrAction>
+            for i in 0..terminals.len() {
+                let action = actions[i + 1];
+                let terminal = terminals[i];
+                table.insert((state, String::from(terminal)), str_to_action(action));
             }
         }
 
@@ -91,6 +117,10 @@ impl ActionTable {
         self.table.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
     pub fn show(&self) {
         for key in self.table.keys() {
             println!("{:?} -> {:?}", key, self.table.get(key).unwrap());
@@ -106,6 +136,12 @@ pub struct GotoTable {
     table: HashMap<(u8, String), u8>,
 }
 
+impl Default for GotoTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GotoTable {
     pub fn new() -> GotoTable {
         let nonterminals = [
@@ -128,6 +164,28 @@ impl GotoTable {
             "R",
             "CABR",
             "CPR",
+            "CABS",
+            "CPS",
+            "LARG",
+            "RE",
+            "CABE",
+            "ENQ",
+            "CPE",
+            "RP",
+            "CABP",
+            "CPP",
+            "SUB",
+            "CABF",
+            "NOMEF",
+            "LPAR",
+            "PAR",
+            "CPF",
+            "RET",
+            "LARGC",
+            "VCTE",
+            "REP",
+            "LDN",
+            "LDE",
         ];
 
         // open the .csv goto table
@@ -144,19 +202,17 @@ impl GotoTable {
         lines.next();
 
         let mut table = HashMap::new();
-        for line in lines {
-            if let Ok(okline) = line {
-                let gotos = okline.split(",").collect::<Vec<&str>>();
-                let state = gotos[0].parse::<u8>().unwrap();
-                // HashMap<(u8, String), u8>
-                for i in 0..nonterminals.len() {
-                    let goto = gotos[i + 1].parse::<u8>().unwrap();
-                    if goto == 0 {
-                        continue;
-                    }
-                    let nonterminal = nonterminals[i];
-                    table.insert((state, String::from(nonterminal)), goto);
+        for line in lines.map_while(Result::ok) {
+            let gotos = line.split(',').collect::<Vec<&str>>();
+            let state = gotos[0].parse::<u8>().unwrap();
+            // HashMap<(u8, String), u8>
+            for i in 0..nonterminals.len() {
+                let goto = gotos[i + 1].parse::<u8>().unwrap();
+                if goto == 0 {
+                    continue;
                 }
+                let nonterminal = nonterminals[i];
+                table.insert((state, String::from(nonterminal)), goto);
             }
         }
 
@@ -167,6 +223,10 @@ impl GotoTable {
         self.table.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
     pub fn show(&self) {
         for key in self.table.keys() {
             println!("{:?} -> {}", key, self.table.get(key).unwrap());
@@ -174,6 +234,6 @@ impl GotoTable {
     }
 
     pub fn get(&self, key: &(u8, String)) -> u8 {
-        self.table.get(key).unwrap().clone()
+        *self.table.get(key).unwrap()
     }
 }
@@ -2,47 +2,111 @@ use std::collections::HashMap;
 
 use super::token::*;
 
-// A struct to represent a Symbol Table. It's just
-// a wrapper of a HashMap<String, Token>.
+// The signature of a MGol subprogram: the tk_type of its return
+// value (None for a 'procedimento') and the tk_type of each one
+// of its parameters.
+#[derive(Clone, Debug)]
+pub struct Subprogram {
+    pub return_type: Option<String>,
+    pub params: Vec<String>,
+}
+
+// A struct to represent a Symbol Table. It's a stack of scopes,
+// each one a HashMap<String, Token>: the first one is the global
+// scope (reserved words and program variables) and a new one is
+// pushed for each subprogram being analyzed. The signatures of the
+// subprograms are kept apart, in a HashMap<String, Subprogram>.
 pub struct SymbolTable {
-    pub hashmap: HashMap<String, Token>,
+    pub scopes: Vec<HashMap<String, Token>>,
+    pub subprograms: HashMap<String, Subprogram>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SymbolTable {
     // create a new SymbolTable (already with the MGol reserved words)
     pub fn new() -> SymbolTable {
-        let hashmap = HashMap::new();
+        let scopes = vec![HashMap::new()];
+        let subprograms = HashMap::new();
 
-        let mut symbol_table = SymbolTable { hashmap };
+        let mut symbol_table = SymbolTable {
+            scopes,
+            subprograms,
+        };
         symbol_table.init_reserved_words();
 
         symbol_table
     }
 
-    // get a Token from the Symbol Table
+    // get a Token from the Symbol Table, looking from the innermost
+    // scope to the global one
     pub fn get(&self, lexeme: String) -> Option<Token> {
-        if let Some(token) = self.hashmap.get(lexeme.as_str()) {
-            return Some(Token::new_from_ref(token));
+        for scope in self.scopes.iter().rev() {
+            if let Some(token) = scope.get(lexeme.as_str()) {
+                return Some(Token::new_from_ref(token));
+            }
         }
 
         None
     }
 
-    // insert a Token into the Symbol Table
+    // insert a Token into the global scope of the Symbol Table
     pub fn insert(&mut self, lexeme: String, token: Token) {
-        self.hashmap.insert(lexeme, token);
+        self.scopes[0].insert(lexeme, token);
     }
 
-    // update a Token from the Symbol Table
+    // insert a Token into the innermost scope of the Symbol Table
+    pub fn declare(&mut self, lexeme: String, token: Token) {
+        self.scopes.last_mut().unwrap().insert(lexeme, token);
+    }
+
+    // update a Token from the Symbol Table, in the innermost scope it is found
     pub fn update(&mut self, lexeme: String, token: Token) {
-        if self.hashmap.contains_key(lexeme.as_str()) {
-            self.insert(lexeme, token);
+        for scope in self.scopes.iter_mut().rev() {
+            if scope.contains_key(lexeme.as_str()) {
+                scope.insert(lexeme, token);
+                return;
+            }
         }
     }
 
-    // return an iteretor of the symbol table hashmap
-    pub fn iter(&self) -> std::collections::hash_map::Iter<String, Token> {
-        self.hashmap.iter()
+    // check whether a lexeme was declared in the innermost scope
+    pub fn is_declared_in_scope(&self, lexeme: &str) -> bool {
+        match self.scopes.last().unwrap().get(lexeme) {
+            Some(token) => token.tk_type.is_some(),
+            None => false,
+        }
+    }
+
+    // open a new scope (when the analysis of a subprogram begins)
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    // close the innermost scope (when the analysis of a subprogram ends)
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    // get the signature of a subprogram
+    pub fn get_subprogram(&self, lexeme: &str) -> Option<Subprogram> {
+        self.subprograms.get(lexeme).cloned()
+    }
+
+    // insert (or replace) the signature of a subprogram
+    pub fn insert_subprogram(&mut self, lexeme: String, subprogram: Subprogram) {
+        self.subprograms.insert(lexeme, subprogram);
+    }
+
+    // return an iteretor of the global scope hashmap
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, String, Token> {
+        self.scopes[0].iter()
     }
 
     // put all the MGol reserved words into the Symbol Table
@@ -55,18 +119,47 @@ impl SymbolTable {
             "leia",
             "se",
             "entao",
+            "senao",
             "fimse",
             "repita",
             "fimrepita",
+            "enquanto",
+            "fimenquanto",
+            "para",
+            "de",
+            "ate",
+            "passo",
+            "fimpara",
+            "funcao",
+            "procedimento",
+            "fimfuncao",
+            "fimprocedimento",
+            "retorne",
             "fim",
             "inteiro",
             "literal",
             "real",
+            "logico",
+            "constante",
+            "e",
+            "ou",
+            "nao",
         ];
 
         for lexeme in reserved_words {
-            self.hashmap
-                .insert(String::from(lexeme), Token::new_from_lexeme(lexeme));
+            self.insert(String::from(lexeme), Token::new_from_lexeme(lexeme));
+        }
+
+        // the logico literals
+        for lexeme in ["verdadeiro", "falso"] {
+            self.insert(
+                String::from(lexeme),
+                Token::new(
+                    String::from("vlog"),
+                    Some(String::from(lexeme)),
+                    Some(String::from("logico")),
+                ),
+            );
         }
     }
 }
@@ -1,12 +1,16 @@
 // A struct to represent a Token: class is just a
 // String, while lexeme and tk_type is a Option<String>.
 // When lexeme or tk_type is None, means that the Token
-// doesn't have those attributes.
+// doesn't have those attributes. The constant attribute keeps
+// the value of a named constant and is None for anything else.
+use super::ir::Operand;
+
 #[derive(Clone, Debug)]
 pub struct Token {
     pub class: String,
     pub lexeme: Option<String>,
     pub tk_type: Option<String>,
+    pub constant: Option<Operand>,
 }
 
 impl Token {
@@ -16,6 +20,7 @@ impl Token {
             class,
             lexeme,
             tk_type,
+            constant: None,
         }
     }
 
@@ -25,6 +30,7 @@ impl Token {
             class: String::from(lexeme),
             lexeme: Some(String::from(lexeme)),
             tk_type: Some(String::from(lexeme)),
+            constant: None,
         }
     }
 
@@ -0,0 +1,217 @@
+// Run a compiled MGol program. The VM reads, writes and stops on the
+// errors the same way the interpreter does, so a program gives the
+// same output either way.
+use std::io::{BufRead, Write};
+use super::bytecode::{Module, Op};
+use super::interpreter::{
+    binary_op, convert, declare, format, integer, too_many_calls, zero, Input, Slot, Value, MAX_CALLS,
+};
+use super::ir::Type;
+
+struct Vm<'a, R: BufRead, W: Write> {
+    module: &'a Module,
+    globals: Vec<Slot>,
+    stack: Vec<Value>,
+    // the functions that called the one running, which waits on top
+    frames: Vec<Frame>,
+    input: Input<R>,
+    output: W,
+}
+
+// a function of the module while it runs: its slots, the instruction
+// it goes on from and where its arguments were on the stack
+struct Frame {
+    function: usize,
+    slots: Vec<Slot>,
+    pc: usize,
+    base: usize,
+}
+
+// the value of a slot, which must not be an array
+fn value(slot: &Slot) -> Result<Value, String> {
+    match slot {
+        Slot::Scalar(value) => Ok(value.clone()),
+        Slot::Array(_) => Err(String::from("Erro: um vetor foi usado como valor no bytecode\n")),
+    }
+}
+
+// the position of an array element, which must be in bounds
+fn element(slot: &mut Slot, index: Value) -> Result<&mut Value, String> {
+    let i = integer(&index);
+    match slot {
+        Slot::Array(values) => {
+            let size = values.len();
+            values.get_mut(i as usize).filter(|_| i >= 0).ok_or_else(|| {
+                format!("Erro: indice {} fora dos limites do vetor [0, {})\n", i, size)
+            })
+        }
+        Slot::Scalar(_) => Err(String::from("Erro: uma variável que não é um vetor foi indexada no bytecode\n")),
+    }
+}
+
+impl<'a, R: BufRead, W: Write> Vm<'a, R, W> {
+    fn pop(&mut self) -> Result<Value, String> {
+        self.stack.pop().ok_or_else(|| String::from("Erro: a pilha do bytecode está vazia\n"))
+    }
+
+    // start a function of the module with its arguments on top of the
+    // stack; it runs in a frame of its own on top of the others
+    fn call(&mut self, n: usize) -> Result<(), String> {
+        if self.frames.len() == MAX_CALLS {
+            return Err(too_many_calls());
+        }
+        let function = &self.module.functions[n];
+        let base = self.stack.len() - function.params;
+        let mut slots: Vec<Slot> = function.slots.iter().map(declare).collect();
+        for (slot, (arg, param)) in slots.iter_mut().zip(self.stack.drain(base..).zip(&function.slots)) {
+            *slot = Slot::Scalar(convert(arg, param.tk_type));
+        }
+        self.frames.push(Frame { function: n, slots, pc: 0, base });
+        Ok(())
+    }
+
+    // Run the frame on top until it calls a function, whose frame goes
+    // on top of it, or returns, leaving what it returned on the stack
+    // of values. A deep recursion takes memory but not the stack of the
+    // VM.
+    fn run_frames(&mut self) -> Result<(), String> {
+        let module = self.module;
+        'frames: while let Some(mut frame) = self.frames.pop() {
+            let function = &module.functions[frame.function];
+            let base = frame.base;
+            let mut pc = frame.pc;
+            while pc < function.code.len() {
+                match function.code[pc] {
+                    Op::PushInt(n) => self.stack.push(Value::Int(n)),
+                    Op::PushReal(x) => self.stack.push(Value::Real(x)),
+                    Op::PushLit(n) => self.stack.push(Value::Lit(module.literals[n as usize].clone())),
+                    Op::Load(slot) => self.stack.push(value(&frame.slots[slot as usize])?),
+                    Op::LoadGlobal(slot) => self.stack.push(value(&self.globals[slot as usize])?),
+                    Op::Store(slot) => {
+                        let value = convert(self.pop()?, function.slots[slot as usize].tk_type);
+                        frame.slots[slot as usize] = Slot::Scalar(value);
+                    }
+                    Op::StoreGlobal(slot) => {
+                        let value = convert(self.pop()?, module.globals[slot as usize].tk_type);
+                        self.globals[slot as usize] = Slot::Scalar(value);
+                    }
+                    Op::LoadElem(slot) => {
+                        let index = self.pop()?;
+                        let value = element(&mut frame.slots[slot as usize], index)?.clone();
+                        self.stack.push(value);
+                    }
+                    Op::LoadGlobalElem(slot) => {
+                        let index = self.pop()?;
+                        let value = element(&mut self.globals[slot as usize], index)?.clone();
+                        self.stack.push(value);
+                    }
+                    Op::StoreElem(slot) => {
+                        let value = convert(self.pop()?, function.slots[slot as usize].tk_type);
+                        let index = self.pop()?;
+                        *element(&mut frame.slots[slot as usize], index)? = value;
+                    }
+                    Op::StoreGlobalElem(slot) => {
+                        let value = convert(self.pop()?, module.globals[slot as usize].tk_type);
+                        let index = self.pop()?;
+                        *element(&mut self.globals[slot as usize], index)? = value;
+                    }
+                    Op::IntToReal => {
+                        let value = self.pop()?;
+                        self.stack.push(convert(value, Type::Real));
+                    }
+                    Op::RealToInt => {
+                        let value = self.pop()?;
+                        self.stack.push(convert(value, Type::Inteiro));
+                    }
+                    Op::Pop => {
+                        self.pop()?;
+                    }
+                    Op::BinInt(operator) | Op::BinReal(operator) => {
+                        let right = self.pop()?;
+                        let left = self.pop()?;
+                        let tk_type = match function.code[pc] {
+                            Op::BinReal(_) => Type::Real,
+                            _ => Type::Inteiro,
+                        };
+                        self.stack.push(binary_op(left, operator, right, tk_type)?);
+                    }
+                    Op::Jump(target) => {
+                        pc = target as usize;
+                        continue;
+                    }
+                    Op::JumpIfFalse(target) => {
+                        if integer(&self.pop()?) == 0 {
+                            pc = target as usize;
+                            continue;
+                        }
+                    }
+                    Op::Read(tk_type) => {
+                        // the output so far is seen before the program waits
+                        let _ = self.output.flush();
+                        let value = self.pop()?;
+                        let value = self.input.read(tk_type).unwrap_or(value);
+                        self.stack.push(value);
+                    }
+                    Op::Write(tk_type) => {
+                        let text = format(self.pop()?, tk_type);
+                        let _ = self.output.write_all(text.as_bytes());
+                    }
+                    Op::Call(n) => {
+                        frame.pc = pc + 1;
+                        self.frames.push(frame);
+                        self.call(n as usize)?;
+                        continue 'frames;
+                    }
+                    Op::Return => break,
+                    Op::ReturnValue => {
+                        let value = self.pop()?;
+                        self.stack.truncate(base);
+                        self.stack.push(convert(value, function.return_type.unwrap_or(Type::Inteiro)));
+                        continue 'frames;
+                    }
+                    Op::CheckIndex(size, row) => {
+                        let i = integer(&self.pop()?);
+                        if i < 0 || i as u32 >= size {
+                            return Err(format!(
+                                "Erro: indice {} fora dos limites do vetor [0, {}) na linha {}\n",
+                                i, size, row
+                            ));
+                        }
+                    }
+                }
+                pc += 1;
+            }
+
+            // a function that ends without returning a value returns the
+            // zero of its type
+            self.stack.truncate(base);
+            if let Some(return_type) = function.return_type {
+                self.stack.push(zero(return_type));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Run the module, reading from input and writing to output, and return
+// its exit status: 1 when it stopped because of an error.
+pub fn run(module: &Module, input: impl BufRead, output: impl Write) -> i32 {
+    let mut vm = Vm {
+        module,
+        globals: module.globals.iter().map(declare).collect(),
+        stack: Vec::new(),
+        frames: Vec::new(),
+        input: Input::new(input),
+        output,
+    };
+
+    let status = match vm.call(module.functions.len() - 1).and_then(|_| vm.run_frames()) {
+        Ok(_) => 0,
+        Err(msg) => {
+            let _ = vm.output.write_all(msg.as_bytes());
+            1
+        }
+    };
+    let _ = vm.output.flush();
+    status
+}
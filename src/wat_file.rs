@@ -0,0 +1,689 @@
+// The WebAssembly text (.wat) of a program, generated from its
+// intermediate code. An inteiro or a logico is an i32 and a real is
+// an f64. A literal or an array is kept in the linear memory and its
+// variable holds its address: the program variables are at fixed
+// addresses and the ones of a subprogram are in a frame of a stack in
+// the memory, so a recursive call has its own.
+//
+// The reads, the writes and the runtime errors are host functions the
+// module imports from "mgol":
+//
+//   escreva_inteiro(i32), escreva_real(f64), escreva_logico(i32)
+//   escreva_literal(i32): the address of a text ending in a '\0'
+//   leia_inteiro(i32) -> i32, leia_real(f64) -> f64, leia_logico(i32) -> i32:
+//       the value read, or the one given when there is none to read
+//   leia_literal(i32): read a word into the 256 bytes at the address,
+//       leaving them as they are when there is none to read
+//   erro_indice(i32, i32, i32): the index, the size of the array and
+//       the line of the program, or 0 when the index is not checked
+//   erro_divisao(): a division of an inteiro by zero
+//
+// and after an error the program traps. The module exports its memory
+// and the main program, as "memory" and "main".
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Write};
+use super::interpreter::unescape;
+use super::ir::{Function, Instr, Operand, Operator, Program, Type, Var};
+
+// the bytes of a literal, with the '\0' that ends it
+const LITERAL_SIZE: u32 = 256;
+// the room for the frames of the subprograms
+const STACK_SIZE: u32 = 1 << 20;
+const PAGE_SIZE: u32 = 1 << 16;
+
+const IMPORTS: [(&str, &str); 10] = [
+    ("escreva_inteiro", "(param i32)"),
+    ("escreva_real", "(param f64)"),
+    ("escreva_literal", "(param i32)"),
+    ("escreva_logico", "(param i32)"),
+    ("leia_inteiro", "(param i32) (result i32)"),
+    ("leia_real", "(param f64) (result f64)"),
+    ("leia_literal", "(param i32)"),
+    ("leia_logico", "(param i32) (result i32)"),
+    ("erro_indice", "(param i32 i32 i32)"),
+    ("erro_divisao", ""),
+];
+
+fn wasm_type(tk_type: Type) -> &'static str {
+    match tk_type {
+        Type::Real => "f64",
+        _ => "i32",
+    }
+}
+
+// the bytes one value of the type takes in memory
+fn byte_size(tk_type: Type) -> u32 {
+    match tk_type {
+        Type::Inteiro | Type::Logico => 4,
+        Type::Real => 8,
+        Type::Literal => LITERAL_SIZE,
+    }
+}
+
+// a literal or an array is in memory and its variable holds the address
+fn in_memory(var: &Var) -> bool {
+    var.size.is_some() || var.tk_type == Type::Literal
+}
+
+// the bytes of a variable in memory, rounded up so the next one is aligned
+fn memory_size(var: &Var) -> u32 {
+    let size = byte_size(var.tk_type) * var.size.unwrap_or(1) as u32;
+    size.div_ceil(8) * 8
+}
+
+// the wasm local of a temporary variable; a name of the program has no '.'
+fn temp_name(n: usize) -> String {
+    format!("temp.{}", n)
+}
+
+// a real constant as WAT writes it
+fn real_constant(x: f64) -> String {
+    if x.is_nan() {
+        String::from("nan")
+    } else {
+        format!("{:?}", x)
+    }
+}
+
+// the bytes of a WAT string, escaped
+fn wat_string(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for byte in bytes {
+        match byte {
+            b'"' | b'\\' => s.push_str(&format!("\\{}", *byte as char)),
+            b' '..=b'~' => s.push(*byte as char),
+            _ => s.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    s
+}
+
+// functions of the module the generated code may call, written only
+// when they are used
+#[derive(PartialEq)]
+enum Helper {
+    CopyLiteral,
+    Index,
+    CheckedIndex,
+    Divide,
+}
+
+// The WAT module: its memory starts with the literal constants, then
+// the program variables that are in memory and then the stack.
+pub struct WatFile {
+    data: Vec<u8>,
+    constants: HashMap<String, u32>,
+    globals: Vec<String>,
+    functions: Vec<String>,
+    stack_base: u32,
+    helpers: Vec<Helper>,
+}
+
+// the code of a subprogram (or of the main program) being generated
+struct Body<'a, 'w> {
+    wat_file: &'w mut WatFile,
+    program: &'a Program,
+    function: &'a Function,
+    lines: Vec<String>,
+}
+
+impl<'a, 'w> Body<'a, 'w> {
+    fn emit(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("    {}", line.into()));
+    }
+
+    // the declaration of a name used in the function and whether its
+    // value (or its address) is in a wasm local or a wasm global
+    fn var(&self, name: &str) -> (&'static str, Var) {
+        if let Some(var) = self.function.get_var(name) {
+            return ("local", var.clone());
+        }
+        match self.program.globals.iter().find(|var| var.name == name) {
+            Some(var) => ("global", var.clone()),
+            None => panic!("Variável '{}' não declarada no código intermediário", name),
+        }
+    }
+
+    // the scope, the wasm name and the declaration of a variable, a
+    // temporary variable or an array element
+    fn place(&self, operand: &Operand) -> (&'static str, String, Var) {
+        match operand {
+            Operand::Var(name) | Operand::Index(name, _) => {
+                let (scope, var) = self.var(name);
+                (scope, name.clone(), var)
+            }
+            Operand::Temp(n) => (
+                "local",
+                temp_name(*n),
+                Var {
+                    name: temp_name(*n),
+                    tk_type: self.function.temps[*n],
+                    size: None,
+                },
+            ),
+            _ => panic!("Uma constante foi usada como destino no código intermediário"),
+        }
+    }
+
+    fn convert(&mut self, from: Type, to: Type) {
+        match (wasm_type(from), wasm_type(to)) {
+            ("i32", "f64") => self.emit("f64.convert_i32_s"),
+            ("f64", "i32") => self.emit("i32.trunc_sat_f64_s"),
+            _ => (),
+        }
+    }
+
+    // push the address of an array element, whose index must be in bounds
+    fn element(&mut self, array: &str, index: &Operand) {
+        let (scope, var) = self.var(array);
+        self.emit(format!("{}.get ${}", scope, array));
+        self.load(index, Type::Inteiro);
+        self.emit(format!("i32.const {}", var.size.unwrap_or(0)));
+        self.wat_file.use_helper(Helper::Index);
+        self.emit("call $mgol.indice");
+        self.emit(format!("i32.const {}", byte_size(var.tk_type)));
+        self.emit("i32.mul");
+        self.emit("i32.add");
+    }
+
+    // push the value of an operand, as a value of type tk_type; for a
+    // literal, its address
+    fn load(&mut self, operand: &Operand, tk_type: Type) {
+        match operand {
+            Operand::Int(n) if tk_type == Type::Real => self.emit(format!("f64.const {}", n)),
+            Operand::Int(n) => self.emit(format!("i32.const {}", n)),
+            Operand::Real(x) if tk_type == Type::Real => self.emit(format!("f64.const {}", real_constant(*x))),
+            Operand::Real(x) => self.emit(format!("i32.const {}", *x as i32)),
+            Operand::Lit(text) => {
+                let address = self.wat_file.constant(unescape(text));
+                self.emit(format!("i32.const {}", address));
+            }
+            Operand::Var(_) | Operand::Temp(_) => {
+                let (scope, name, var) = self.place(operand);
+                self.emit(format!("{}.get ${}", scope, name));
+                self.convert(var.tk_type, tk_type);
+            }
+            Operand::Index(array, index) => {
+                self.element(array, index);
+                let (_, var) = self.var(array);
+                if var.tk_type != Type::Literal {
+                    self.emit(format!("{}.load", wasm_type(var.tk_type)));
+                }
+                self.convert(var.tk_type, tk_type);
+            }
+        }
+    }
+
+    // the address a value is stored at goes below the value: an array
+    // element or a literal
+    fn push_address(&mut self, target: &Operand) {
+        match target {
+            Operand::Index(array, index) => self.element(array, index),
+            Operand::Var(_) | Operand::Temp(_) => {
+                let (scope, name, var) = self.place(target);
+                if in_memory(&var) {
+                    self.emit(format!("{}.get ${}", scope, name));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // pop a value of type tk_type into target, after push_address
+    fn store(&mut self, target: &Operand, tk_type: Type) {
+        let (scope, name, var) = self.place(target);
+        if var.tk_type == Type::Literal {
+            self.wat_file.use_helper(Helper::CopyLiteral);
+            self.emit("call $mgol.copia");
+            return;
+        }
+        self.convert(tk_type, var.tk_type);
+        match target {
+            Operand::Index(..) => self.emit(format!("{}.store", wasm_type(var.tk_type))),
+            _ => self.emit(format!("{}.set ${}", scope, name)),
+        }
+    }
+
+    fn operator(&mut self, operator: Operator, tk_type: Type) {
+        let wasm = wasm_type(tk_type);
+        // an inteiro is signed
+        let signed = if wasm == "i32" { "_s" } else { "" };
+        let instr = match operator {
+            Operator::Add => format!("{}.add", wasm),
+            Operator::Sub => format!("{}.sub", wasm),
+            Operator::Mul => format!("{}.mul", wasm),
+            Operator::Div if wasm == "i32" => {
+                self.wat_file.use_helper(Helper::Divide);
+                String::from("call $mgol.divide")
+            }
+            Operator::Div => String::from("f64.div"),
+            Operator::Lt => format!("{}.lt{}", wasm, signed),
+            Operator::Le => format!("{}.le{}", wasm, signed),
+            Operator::Gt => format!("{}.gt{}", wasm, signed),
+            Operator::Ge => format!("{}.ge{}", wasm, signed),
+            Operator::Eq => format!("{}.eq", wasm),
+            Operator::Ne => format!("{}.ne", wasm),
+            // a logico is 1 or 0
+            Operator::And => String::from("i32.and"),
+            Operator::Or => String::from("i32.or"),
+        };
+        self.emit(instr);
+    }
+
+    // give the frame back to the stack, before the function returns
+    fn leave(&mut self, frame_size: u32) {
+        if frame_size > 0 {
+            self.emit("local.get $mgol.fp");
+            self.emit("global.set $mgol.sp");
+        }
+    }
+
+    // The code of the function. Its labels become the ends of nested
+    // blocks in a loop, with a br_table at the start that goes to the
+    // block ending where the code should go on, so a goto sets the
+    // next block in $mgol.pc and starts the loop again.
+    fn code(&mut self, frame_size: u32) {
+        let (program, function) = (self.program, self.function);
+        let used_labels: HashSet<usize> = function
+            .code
+            .iter()
+            .filter_map(|instr| match instr {
+                Instr::Goto(label) | Instr::IfFalse(_, label) => Some(*label),
+                _ => None,
+            })
+            .collect();
+        let labels: Vec<usize> = function
+            .code
+            .iter()
+            .filter_map(|instr| match instr {
+                Instr::Label(label) if used_labels.contains(label) => Some(*label),
+                _ => None,
+            })
+            .collect();
+        // the block that ends at a label is the one br_table goes to
+        // with that number
+        let block = |label: usize| labels.iter().position(|l| *l == label).unwrap() + 1;
+
+        if !labels.is_empty() {
+            self.emit("loop $mgol.inicio");
+            for label in labels.iter().rev() {
+                self.emit(format!("block $L{}", label));
+            }
+            self.emit("block $mgol.entrada");
+            self.emit("local.get $mgol.pc");
+            let targets: Vec<String> = labels.iter().map(|label| format!("$L{}", label)).collect();
+            self.emit(format!("br_table $mgol.entrada {}", targets.join(" ")));
+            self.emit("end $mgol.entrada");
+        }
+
+        for instr in &function.code {
+            match instr {
+                Instr::Assign(target, value, _) => {
+                    let value_type = program.type_of(function, value);
+                    self.push_address(target);
+                    self.load(value, value_type);
+                    self.store(target, value_type);
+                }
+                Instr::BinOp(target, left, operator, right, tk_type) => {
+                    self.push_address(target);
+                    self.load(left, *tk_type);
+                    self.load(right, *tk_type);
+                    self.operator(*operator, *tk_type);
+                    let result = if operator.is_relational() {
+                        Type::Logico
+                    } else {
+                        *tk_type
+                    };
+                    self.store(target, result);
+                }
+                Instr::Read(target, Type::Literal) => {
+                    self.push_address(target);
+                    self.emit("call $mgol.leia_literal");
+                }
+                Instr::Read(target, tk_type) => {
+                    self.push_address(target);
+                    self.load(target, *tk_type);
+                    self.emit(format!("call $mgol.leia_{}", tk_type));
+                    self.store(target, *tk_type);
+                }
+                Instr::Write(value, tk_type) => {
+                    self.load(value, *tk_type);
+                    self.emit(format!("call $mgol.escreva_{}", tk_type));
+                }
+                Instr::Label(label) => {
+                    if used_labels.contains(label) {
+                        self.emit(format!("end $L{}", label));
+                    }
+                }
+                Instr::Goto(label) => {
+                    self.emit(format!("i32.const {}", block(*label)));
+                    self.emit("local.set $mgol.pc");
+                    self.emit("br $mgol.inicio");
+                }
+                Instr::IfFalse(condition, label) => {
+                    self.emit(format!("i32.const {}", block(*label)));
+                    self.emit("local.set $mgol.pc");
+                    self.load(condition, Type::Logico);
+                    self.emit("i32.eqz");
+                    self.emit("br_if $mgol.inicio");
+                }
+                Instr::Call(target, name, args) => {
+                    let callee = program
+                        .subprograms
+                        .iter()
+                        .find(|function| function.name == *name)
+                        .unwrap_or_else(|| panic!("Subprograma '{}' não definido no código intermediário", name));
+                    if let Some(target) = target {
+                        self.push_address(target);
+                    }
+                    for (arg, param) in args.iter().zip(&callee.params) {
+                        self.load(arg, param.tk_type);
+                    }
+                    self.emit(format!("call ${}", name));
+                    match (target, callee.return_type) {
+                        (Some(target), Some(return_type)) => self.store(target, return_type),
+                        (None, Some(_)) => self.emit("drop"),
+                        _ => (),
+                    }
+                }
+                Instr::Return(value) => {
+                    if let Some(value) = value {
+                        self.load(value, function.return_type.unwrap_or(Type::Inteiro));
+                    }
+                    self.leave(frame_size);
+                    self.emit("return");
+                }
+                Instr::CheckIndex(index, size, row) => {
+                    self.load(index, Type::Inteiro);
+                    self.emit(format!("i32.const {}", size));
+                    self.emit(format!("i32.const {}", row));
+                    self.wat_file.use_helper(Helper::CheckedIndex);
+                    self.emit("call $mgol.verifica_indice");
+                }
+            }
+        }
+
+        if !labels.is_empty() {
+            self.emit("end $mgol.inicio");
+        }
+        // a function that ends without returning a value returns the
+        // zero of its type
+        self.leave(frame_size);
+        if let Some(return_type) = function.return_type {
+            self.emit(format!("{}.const 0", wasm_type(return_type)));
+        }
+    }
+}
+
+impl WatFile {
+    pub fn new(program: &Program) -> WatFile {
+        let mut wat_file = WatFile {
+            data: Vec::new(),
+            constants: HashMap::new(),
+            globals: Vec::new(),
+            functions: Vec::new(),
+            stack_base: 0,
+            helpers: Vec::new(),
+        };
+
+        for function in &program.subprograms {
+            let text = wat_file.function(program, function, format!("${}", function.name));
+            wat_file.functions.push(text);
+        }
+        let text = wat_file.function(program, &program.main, String::from("$mgol.programa (export \"main\")"));
+        wat_file.functions.push(text);
+
+        // the program variables in memory come after the constants
+        let mut address = (wat_file.data.len() as u32).div_ceil(8) * 8;
+        for var in &program.globals {
+            wat_file.globals.push(if in_memory(var) {
+                let global = format!("(global ${} i32 (i32.const {}))", var.name, address);
+                address += memory_size(var);
+                global
+            } else {
+                let wasm = wasm_type(var.tk_type);
+                format!("(global ${} (mut {}) ({}.const 0))", var.name, wasm, wasm)
+            });
+        }
+        wat_file.stack_base = address;
+
+        wat_file
+    }
+
+    fn use_helper(&mut self, helper: Helper) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+        }
+    }
+
+    // the address of a literal constant, ending in a '\0'
+    fn constant(&mut self, text: String) -> u32 {
+        if let Some(address) = self.constants.get(&text) {
+            return *address;
+        }
+        let address = self.data.len() as u32;
+        self.data.extend_from_slice(text.as_bytes());
+        self.data.push(0);
+        self.constants.insert(text, address);
+        address
+    }
+
+    fn function(&mut self, program: &Program, function: &Function, header: String) -> String {
+        let temps: Vec<Var> = function
+            .temps
+            .iter()
+            .enumerate()
+            .map(|(n, tk_type)| Var {
+                name: temp_name(n),
+                tk_type: *tk_type,
+                size: None,
+            })
+            .collect();
+
+        // the literals and the arrays of the function are in its frame
+        let mut frame = Vec::new();
+        let mut frame_size = 0;
+        for var in function.params.iter().chain(&function.locals).chain(&temps) {
+            if in_memory(var) {
+                frame.push((var, frame_size));
+                frame_size += memory_size(var);
+            }
+        }
+
+        let mut lines = vec![format!("  (func {}", header)];
+        for param in &function.params {
+            lines.push(format!("    (param ${} {})", param.name, wasm_type(param.tk_type)));
+        }
+        if let Some(return_type) = function.return_type {
+            lines.push(format!("    (result {})", wasm_type(return_type)));
+        }
+        for var in function.locals.iter().chain(&temps) {
+            let wasm = if in_memory(var) { "i32" } else { wasm_type(var.tk_type) };
+            lines.push(format!("    (local ${} {})", var.name, wasm));
+        }
+        if frame_size > 0 {
+            lines.push(String::from("    (local $mgol.fp i32)"));
+        }
+        let has_labels = function.code.iter().any(|instr| matches!(instr, Instr::Goto(_) | Instr::IfFalse(..)));
+        if has_labels {
+            lines.push(String::from("    (local $mgol.pc i32)"));
+        }
+
+        let mut body = Body {
+            wat_file: self,
+            program,
+            function,
+            lines,
+        };
+        if frame_size > 0 {
+            // the frame starts with zeros, like every variable
+            body.emit("global.get $mgol.sp");
+            body.emit("local.tee $mgol.fp");
+            body.emit("i32.const 0");
+            body.emit(format!("i32.const {}", frame_size));
+            body.emit("memory.fill");
+            body.emit("local.get $mgol.fp");
+            body.emit(format!("i32.const {}", frame_size));
+            body.emit("i32.add");
+            body.emit("global.set $mgol.sp");
+            for (var, offset) in &frame {
+                // a literal argument is copied into the frame
+                let is_param = function.params.iter().any(|param| param.name == var.name);
+                if is_param {
+                    body.emit("local.get $mgol.fp");
+                    body.emit(format!("i32.const {}", offset));
+                    body.emit("i32.add");
+                    body.emit(format!("local.get ${}", var.name));
+                    body.wat_file.use_helper(Helper::CopyLiteral);
+                    body.emit("call $mgol.copia");
+                }
+                body.emit("local.get $mgol.fp");
+                body.emit(format!("i32.const {}", offset));
+                body.emit("i32.add");
+                body.emit(format!("local.set ${}", var.name));
+            }
+        }
+        body.code(frame_size);
+        let mut lines = body.lines;
+        lines.push(String::from("  )"));
+        lines.join("\n")
+    }
+
+    fn write_file(&self, file: &mut File) -> io::Result<()> {
+        writeln!(file, "(module")?;
+        for (name, signature) in IMPORTS {
+            let signature = if signature.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", signature)
+            };
+            writeln!(file, "  (import \"mgol\" \"{}\" (func $mgol.{}{}))", name, name, signature)?;
+        }
+        writeln!(file)?;
+
+        let pages = (self.stack_base + STACK_SIZE).div_ceil(PAGE_SIZE);
+        writeln!(file, "  (memory (export \"memory\") {})", pages)?;
+        if !self.data.is_empty() {
+            writeln!(file, "  (data (i32.const 0) \"{}\")", wat_string(&self.data))?;
+        }
+        writeln!(file, "  (global $mgol.sp (mut i32) (i32.const {}))", self.stack_base)?;
+        for global in &self.globals {
+            writeln!(file, "  {}", global)?;
+        }
+        writeln!(file)?;
+
+        for helper in &self.helpers {
+            let lines: &[&str] = match helper {
+                // copy a literal, cut to 255 bytes, like C's strncpy would
+                Helper::CopyLiteral => &[
+                    "(func $mgol.copia (param $destino i32) (param $origem i32)",
+                    "  (local $n i32)",
+                    "  block $fim",
+                    "  loop $conta",
+                    "  local.get $n",
+                    "  i32.const 255",
+                    "  i32.ge_u",
+                    "  br_if $fim",
+                    "  local.get $origem",
+                    "  local.get $n",
+                    "  i32.add",
+                    "  i32.load8_u",
+                    "  i32.eqz",
+                    "  br_if $fim",
+                    "  local.get $n",
+                    "  i32.const 1",
+                    "  i32.add",
+                    "  local.set $n",
+                    "  br $conta",
+                    "  end $conta",
+                    "  end $fim",
+                    "  local.get $destino",
+                    "  local.get $origem",
+                    "  local.get $n",
+                    "  memory.copy",
+                    "  local.get $destino",
+                    "  local.get $n",
+                    "  i32.add",
+                    "  i32.const 0",
+                    "  i32.store8",
+                    ")",
+                ],
+                Helper::Index => &[
+                    "(func $mgol.indice (param $i i32) (param $n i32) (result i32)",
+                    "  local.get $i",
+                    "  local.get $n",
+                    "  i32.ge_u",
+                    "  if",
+                    "  local.get $i",
+                    "  local.get $n",
+                    "  i32.const 0",
+                    "  call $mgol.erro_indice",
+                    "  unreachable",
+                    "  end",
+                    "  local.get $i",
+                    ")",
+                ],
+                Helper::CheckedIndex => &[
+                    "(func $mgol.verifica_indice (param $i i32) (param $n i32) (param $linha i32)",
+                    "  local.get $i",
+                    "  local.get $n",
+                    "  i32.ge_u",
+                    "  if",
+                    "  local.get $i",
+                    "  local.get $n",
+                    "  local.get $linha",
+                    "  call $mgol.erro_indice",
+                    "  unreachable",
+                    "  end",
+                    ")",
+                ],
+                // a division by -1 would trap on the smallest inteiro
+                Helper::Divide => &[
+                    "(func $mgol.divide (param $a i32) (param $b i32) (result i32)",
+                    "  local.get $b",
+                    "  i32.eqz",
+                    "  if",
+                    "  call $mgol.erro_divisao",
+                    "  unreachable",
+                    "  end",
+                    "  local.get $b",
+                    "  i32.const -1",
+                    "  i32.eq",
+                    "  if",
+                    "  i32.const 0",
+                    "  local.get $a",
+                    "  i32.sub",
+                    "  return",
+                    "  end",
+                    "  local.get $a",
+                    "  local.get $b",
+                    "  i32.div_s",
+                    ")",
+                ],
+            };
+            for line in lines {
+                writeln!(file, "  {}", line)?;
+            }
+            writeln!(file)?;
+        }
+
+        for function in &self.functions {
+            writeln!(file, "{}", function)?;
+            writeln!(file)?;
+        }
+        writeln!(file, ")")
+    }
+
+    // write the WAT module to path
+    pub fn create(&self, path: &str) {
+        let mut file = match File::create(path) {
+            Err(_) => panic!("Não foi possível criar o módulo WebAssembly {}", path),
+            Ok(file) => file,
+        };
+
+        if self.write_file(&mut file).is_err() {
+            panic!("Não foi possível escrever o módulo WebAssembly {}", path);
+        }
+    }
+}
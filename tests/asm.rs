@@ -0,0 +1,88 @@
+// A program compiled with --emit=asm is x86-64 assembly that cc builds
+// into an executable, which reads, writes and stops on errors the same
+// as 'mgol run'.
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    literal nome;
+    inteiro v[3];
+    inteiro n;
+    inteiro z;
+    real a;
+    real b;
+    logico l;
+  varfim;
+  procedimento mostra(literal s, inteiro k)
+    varinicio
+      literal t;
+      real w[2];
+    varfim;
+    t <- s;
+    w[1] <- 2.5;
+    escreva t, \" \", w[1], \" \", w[0], \"\\n\";
+    se (k > 0) entao
+      leia t;
+      mostra(t, k - 1);
+      escreva t, \"\\n\";
+    fimse
+  fimprocedimento
+  leia nome;
+  mostra(nome, 2);
+  escreva nome, \"\\n\";
+  leia a;
+  leia b;
+  l <- a <= b;
+  a <- a * b;
+  escreva l, \" \", a, \"\\n\";
+  leia n;
+  v[n] <- 5;
+  escreva v[n], \"\\n\";
+  leia z;
+  n <- n / z;
+  escreva n;
+fim
+";
+
+#[test]
+fn runs_like_the_interpreter() {
+    let inputs = [
+        "ana bia cid 1.5 2.5 2 1",
+        "ana bia cid 2.5 1.5 1 -1",
+        "ana bia cid 1 1 3 1",
+        "ana bia cid 1 1 1 0",
+    ];
+    for options in [&[][..], &["--bounds-check"][..]] {
+        for input in inputs {
+            let out = common::run_asm(&format!("asm{}", options.concat()), PROGRAMA, input, options);
+            let expected = common::interpret("asm-interpretador", PROGRAMA, input, options);
+            assert_eq!(out, expected, "entrada {} com {:?}", input, options);
+        }
+    }
+}
+
+#[test]
+fn corpus_runs_like_the_obj_file() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programas");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mgol"))
+        .collect();
+    programs.sort();
+
+    for path in programs {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        let input = fs::read_to_string(path.with_extension("entrada")).unwrap_or_default();
+        let expected = common::run(&format!("asm-{}-c", name), &source, &input);
+        for options in [&[][..], &["--bounds-check"][..], &["-O"][..]] {
+            let (out, status) = common::run_asm(&format!("asm-{}{}", name, options.concat()), &source, &input, options);
+            assert_eq!(status, 0);
+            assert_eq!(out, expected, "saída de {} com {:?}", name, options);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// A program compiled with --emit=mgb runs from its .mgb file, without
+// being analysed again, the same as with 'mgol run'.
+mod common;
+
+use std::fs;
+use std::process::Command;
+use mgol::bytecode::{Module, Op};
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    inteiro n;
+    real x;
+    literal nome;
+    inteiro v[3];
+  varfim;
+  leia n;
+  leia x;
+  leia nome;
+  v[n] <- n * 2;
+  x <- x / 4.0;
+  escreva nome, \" \", v[n], \" \", x, \"\\n\";
+fim
+";
+
+// run mgol with the given arguments and return its exit status and
+// what it wrote to the standard output and to the standard error
+fn mgol(args: &[&std::ffi::OsStr]) -> (i32, String, String) {
+    let out = Command::new(env!("CARGO_BIN_EXE_mgol"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        out.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+        String::from_utf8_lossy(&out.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn runs_like_the_interpreter() {
+    let mgb_path = common::bytecode("bytecode", PROGRAMA, &[]);
+    for input in ["2 3 maria", "0 -1.5 jose"] {
+        let out = common::run_bytecode(&mgb_path, input);
+        assert_eq!(out, common::interpret("bytecode", PROGRAMA, input, &[]));
+        assert_eq!(out.1, 0);
+    }
+
+    let mgb_path = common::bytecode("bytecode-limites", PROGRAMA, &["--bounds-check"]);
+    let (out, status) = common::run_bytecode(&mgb_path, "3 1.0 ana");
+    assert_eq!(status, 1);
+    assert!(out.starts_with("Erro: indice 3 fora dos limites do vetor [0, 3) na linha"), "{}", out);
+}
+
+#[test]
+fn disassembly_names_what_is_used() {
+    let mgb_path = common::bytecode("bytecode-dis", PROGRAMA, &[]);
+    let (status, out, _) = mgol(&["dis".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!(status, 0);
+    assert!(out.starts_with("mgb versão 1\n"), "{}", out);
+    assert!(out.contains("    global 3: inteiro v[3]\n"), "{}", out);
+    assert!(out.contains("read inteiro"), "{}", out);
+    assert!(out.contains("load_global 0           ; n\n"), "{}", out);
+    assert!(out.contains("store_global_elem 3     ; v\n"), "{}", out);
+    assert!(out.contains("bin_real /"), "{}", out);
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let mgb_path = common::bytecode("bytecode-invalido", PROGRAMA, &[]);
+    let bytes = fs::read(&mgb_path).unwrap();
+
+    // another version of the format
+    let mut other_version = bytes.clone();
+    other_version[4] = 9;
+    fs::write(&mgb_path, &other_version).unwrap();
+    let (status, out, err) = mgol(&["run".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!((status, out.as_str()), (1, ""));
+    assert!(err.contains("a versão 9 do formato não é suportada"), "{}", err);
+
+    // a file cut short
+    fs::write(&mgb_path, &bytes[..bytes.len() - 3]).unwrap();
+    let (status, _, err) = mgol(&["run".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!(status, 1);
+    assert!(err.contains("o arquivo termina antes do esperado"), "{}", err);
+
+    // not a .mgb file at all
+    fs::write(&mgb_path, PROGRAMA).unwrap();
+    let (status, _, err) = mgol(&["dis".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!(status, 1);
+    assert!(err.contains("não é um arquivo .mgb"), "{}", err);
+}
+
+#[test]
+fn code_that_empties_the_stack_is_rejected() {
+    let mgb_path = common::bytecode("bytecode-pilha", PROGRAMA, &[]);
+    let mut module = Module::from_bytes(&fs::read(&mgb_path).unwrap()).unwrap();
+
+    // the main program stores a value it already took from the stack
+    let main = module.functions.last_mut().unwrap();
+    main.code = vec![Op::LoadGlobal(0), Op::Pop, Op::StoreGlobal(0)];
+    fs::write(&mgb_path, module.to_bytes()).unwrap();
+    let (status, out, err) = mgol(&["run".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!((status, out.as_str()), (1, ""));
+    assert!(
+        err.contains("a instrução 2 de 'main' tira mais valores da pilha do que ela tem: store_global 0"),
+        "{}",
+        err
+    );
+
+    // a loop that leaves one more value on the stack each time
+    let main = module.functions.last_mut().unwrap();
+    main.code = vec![Op::PushInt(1), Op::Jump(0)];
+    fs::write(&mgb_path, module.to_bytes()).unwrap();
+    let (status, _, err) = mgol(&["run".as_ref(), mgb_path.as_os_str()]);
+    assert_eq!(status, 1);
+    assert!(err.contains("a pilha chega à instrução 0 de 'main' com alturas diferentes"), "{}", err);
+}
+
+#[test]
+fn arrays_used_as_values_are_rejected() {
+    let mgb_path = common::bytecode("bytecode-vetor", PROGRAMA, &[]);
+    let mut module = Module::from_bytes(&fs::read(&mgb_path).unwrap()).unwrap();
+
+    // the global 3 is the array v
+    for (code, msg) in [
+        (
+            vec![Op::LoadGlobal(3), Op::Pop, Op::Return],
+            "instrução 0 inválida em 'main': load_global 3",
+        ),
+        (
+            vec![Op::PushInt(1), Op::StoreGlobal(3), Op::Return],
+            "instrução 1 inválida em 'main': store_global 3",
+        ),
+    ] {
+        module.functions.last_mut().unwrap().code = code;
+        fs::write(&mgb_path, module.to_bytes()).unwrap();
+        let (status, out, err) = mgol(&["run".as_ref(), mgb_path.as_os_str()]);
+        assert_eq!((status, out.as_str()), (1, ""));
+        assert!(err.contains(msg), "{}", err);
+    }
+}
+
+#[test]
+fn deep_recursion_runs_on_frames_of_the_vm() {
+    let source = "inicio
+  varinicio
+    inteiro n;
+  varfim;
+  funcao inteiro soma(inteiro n)
+    varinicio
+      inteiro k;
+    varfim;
+    se (n <= 0) entao
+      retorne 0;
+    fimse
+    k <- n - 1;
+    k <- soma(k);
+    retorne k + 1;
+  fimfuncao
+  leia n;
+  n <- soma(n);
+  escreva n, \"\\n\";
+fim
+";
+    let mgb_path = common::bytecode("bytecode-recursao", source, &[]);
+    assert_eq!(common::run_bytecode(&mgb_path, "50000"), (String::from("50000\n"), 0));
+
+    let mgb_path = common::bytecode(
+        "bytecode-recursao-infinita",
+        "inicio\n  varinicio\n  varfim;\n  procedimento p()\n    p();\n  fimprocedimento\n  p();\nfim\n",
+        &[],
+    );
+    let (out, status) = common::run_bytecode(&mgb_path, "");
+    assert_eq!(status, 1);
+    assert!(out.starts_with("Erro: mais de 200000 chamadas de subprogramas em andamento"), "{}", out);
+}
@@ -0,0 +1,231 @@
+// Helpers for the tests that compile a MGol program, build the
+// obj file with the system C compiler and run it.
+#![allow(dead_code)]
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// a directory of its own for each test, since they run in parallel
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mgol-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Compile a MGol program and return the obj file path, or the
+// compiler messages when no obj file was created. The compiler runs
+// from the crate root, where the SLR tables are.
+pub fn mgol(name: &str, source: &str) -> Result<PathBuf, String> {
+    mgol_with(name, source, &[])
+}
+
+// the same as mgol, with extra command line options
+pub fn mgol_with(name: &str, source: &str, options: &[&str]) -> Result<PathBuf, String> {
+    let (c_path, out) = compile(name, source.as_bytes(), options);
+    if c_path.exists() {
+        Ok(c_path)
+    } else {
+        Err(out)
+    }
+}
+
+// the obj file of a MGol program that must have no errors
+pub fn obj_file(name: &str, source: &str) -> String {
+    let c_path = mgol(name, source).unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    fs::read_to_string(c_path).unwrap()
+}
+
+// the same as mgol_with, but return what the compiler wrote
+pub fn mgol_stdout(name: &str, source: &str, options: &[&str]) -> String {
+    compile(name, source.as_bytes(), options).1
+}
+
+// the same as mgol_with, for a source file given byte by byte (one
+// that is not in UTF-8, for instance)
+pub fn mgol_bytes(name: &str, source: &[u8], options: &[&str]) -> Result<PathBuf, String> {
+    let (c_path, out) = compile(name, source, options);
+    if c_path.exists() {
+        Ok(c_path)
+    } else {
+        Err(out)
+    }
+}
+
+fn compile(name: &str, source: &[u8], options: &[&str]) -> (PathBuf, String) {
+    let dir = work_dir(name);
+    let source_path = dir.join("programa.mgol");
+    let c_path = dir.join("programa.c");
+    fs::write(&source_path, source).unwrap();
+    let _ = fs::remove_file(&c_path);
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mgol"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg(&source_path)
+        .arg("-o")
+        .arg(&c_path)
+        .args(options)
+        .output()
+        .unwrap();
+
+    (c_path, String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+// build the obj file of a MGol program with cc and return the executable path
+pub fn build(name: &str, source: &str) -> PathBuf {
+    build_with(name, source, &[])
+}
+
+// The same as build, with extra command line options. The obj file
+// must be valid C99 without a single warning.
+pub fn build_with(name: &str, source: &str, options: &[&str]) -> PathBuf {
+    let c_path = mgol_with(name, source, options)
+        .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    let exe_path = c_path.with_extension("out");
+
+    let out = Command::new("cc")
+        .args(["-std=c99", "-Wall", "-Werror"])
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&c_path)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "cc rejeitou o código gerado:\n{}\n{}",
+        String::from_utf8_lossy(&out.stderr),
+        fs::read_to_string(&c_path).unwrap()
+    );
+
+    exe_path
+}
+
+// compile and run a MGol program with the given input and return what it wrote
+pub fn run(name: &str, source: &str, input: &str) -> String {
+    run_with(name, source, input, &[])
+}
+
+// the same as run, with extra command line options
+pub fn run_with(name: &str, source: &str, input: &str, options: &[&str]) -> String {
+    let exe_path = build_with(name, source, options);
+
+    let mut child = Command::new(&exe_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    String::from_utf8_lossy(&out.stdout).into_owned()
+}
+
+// Run a MGol program with 'mgol run', with the given input, and return
+// what it wrote and its exit status.
+pub fn interpret(name: &str, source: &str, input: &str, options: &[&str]) -> (String, i32) {
+    let dir = work_dir(name);
+    let source_path = dir.join("programa.mgol");
+    fs::write(&source_path, source).unwrap();
+
+    let mut args = vec![source_path.into_os_string()];
+    args.extend(options.iter().map(OsString::from));
+    mgol_run(&args, input)
+}
+
+// Compile a MGol program to bytecode with --emit=mgb and return the
+// .mgb file path, which must have been created.
+pub fn bytecode(name: &str, source: &str, options: &[&str]) -> PathBuf {
+    let (c_path, out) = compile(name, source.as_bytes(), &[options, &["--emit=mgb"]].concat());
+    assert!(c_path.exists(), "erros na compilação:\n{}", out);
+    let mgb_path = c_path.with_extension("mgb");
+    fs::rename(&c_path, &mgb_path).unwrap();
+    mgb_path
+}
+
+// Compile a MGol program to a WebAssembly module with --emit=wat and
+// return the .wat file path, which must have been created.
+pub fn wat(name: &str, source: &str, options: &[&str]) -> PathBuf {
+    let (c_path, out) = compile(name, source.as_bytes(), &[options, &["--emit=wat"]].concat());
+    assert!(c_path.exists(), "erros na compilação:\n{}", out);
+    let wat_path = c_path.with_extension("wat");
+    fs::rename(&c_path, &wat_path).unwrap();
+    wat_path
+}
+
+// Compile a MGol program to x86-64 assembly with --emit=asm, build it
+// with cc and run it with the given input, returning what it wrote and
+// its exit status.
+pub fn run_asm(name: &str, source: &str, input: &str, options: &[&str]) -> (String, i32) {
+    let (c_path, out) = compile(name, source.as_bytes(), &[options, &["--emit=asm"]].concat());
+    assert!(c_path.exists(), "erros na compilação:\n{}", out);
+    let asm_path = c_path.with_extension("s");
+    fs::rename(&c_path, &asm_path).unwrap();
+    let exe_path = asm_path.with_extension("out");
+
+    let out = Command::new("cc")
+        .args(["-Wall", "-Werror"])
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&asm_path)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "cc rejeitou o código de montagem gerado:\n{}\n{}",
+        String::from_utf8_lossy(&out.stderr),
+        fs::read_to_string(&asm_path).unwrap()
+    );
+
+    let mut child = Command::new(&exe_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    (
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+        out.status.code().unwrap_or(-1),
+    )
+}
+
+// run a .mgb file with 'mgol run' and return what it wrote and its exit status
+pub fn run_bytecode(mgb_path: &Path, input: &str) -> (String, i32) {
+    mgol_run(&[mgb_path.as_os_str().to_owned()], input)
+}
+
+fn mgol_run(args: &[OsString], input: &str) -> (String, i32) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mgol"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("run")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    (
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+        out.status.code().unwrap_or(-1),
+    )
+}
@@ -0,0 +1,58 @@
+// 'se ... entao ... senao ... fimse': the 'senao' branch runs when the
+// condition is false, and a misplaced 'senao' is dropped with an error.
+mod common;
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    inteiro n;
+  varfim;
+  leia n;
+  se (n > 1) entao
+    escreva \"maior\";
+  senao
+    escreva \"menor ou igual\";
+  fimse
+  escreva \"\\n\";
+fim
+";
+
+#[test]
+fn senao_runs_when_the_condition_is_false() {
+    assert_eq!(common::run("senao-verdadeiro", PROGRAMA, "2"), "maior\n");
+    assert_eq!(common::run("senao-falso", PROGRAMA, "1"), "menor ou igual\n");
+}
+
+#[test]
+fn senao_without_se_is_dropped() {
+    let out = common::mgol_stdout(
+        "senao-sem-se",
+        "inicio\n  varinicio\n  varfim;\n  escreva \"a\";\n  senao\n  escreva \"b\";\nfim\n",
+        &[],
+    );
+    assert!(out.contains("[ES18] Erro sintático na linha 5"), "{}", out);
+    assert!(!out.contains("[ES0]"), "{}", out);
+}
+
+#[test]
+fn second_senao_is_dropped() {
+    let out = common::mgol_stdout(
+        "senao-duplo",
+        "inicio
+  varinicio
+    inteiro n;
+  varfim;
+  leia n;
+  se (n > 1) entao
+    escreva \"a\";
+  senao
+    escreva \"b\";
+  senao
+    escreva \"c\";
+  fimse
+fim
+",
+        &[],
+    );
+    assert!(out.contains("[ES19] Erro sintático na linha 10"), "{}", out);
+    assert!(!out.contains("[ES0]"), "{}", out);
+}
@@ -0,0 +1,37 @@
+// The programs in tests/programas/erros are wrong: the compiler must
+// write exactly the messages of x.erros for x.mgol, one per line, and
+// no obj file.
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn corpus_errors_are_reported() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("programas")
+        .join("erros");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mgol"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "nenhum programa em {}", dir.display());
+
+    for path in programs {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        let expected = fs::read_to_string(path.with_extension("erros")).unwrap();
+
+        let msgs = common::mgol(&format!("erros-{}", name), &source)
+            .expect_err(&format!("{} foi compilado sem erros", name));
+        let errors: Vec<&str> = msgs
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("[E"))
+            .collect();
+        assert_eq!(errors, expected.lines().collect::<Vec<_>>(), "erros de {}", name);
+    }
+}
@@ -0,0 +1,54 @@
+mod common;
+
+#[test]
+fn literal_with_conversion_specifications() {
+    let out = common::run(
+        "literal_com_especificacoes",
+        r#"inicio
+  varinicio
+  varfim;
+  escreva "100% correto: %d %s %n %% %\n";
+fim
+"#,
+        "",
+    );
+
+    assert_eq!(out, "100% correto: %d %s %n %% %\n");
+}
+
+#[test]
+fn literal_with_percent_between_arguments() {
+    let out = common::run(
+        "porcentagem_entre_argumentos",
+        r#"inicio
+  varinicio
+    inteiro a;
+    literal s;
+  varfim;
+  leia a;
+  leia s;
+  escreva "%d = ", a, "%, %s = ", s, "%lf\n";
+fim
+"#,
+        "50 texto\n",
+    );
+
+    assert_eq!(out, "%d = 50%, %s = texto%lf\n");
+}
+
+#[test]
+fn constant_literal_with_percent() {
+    let out = common::run(
+        "constante_com_porcentagem",
+        r#"inicio
+  varinicio
+    constante M <- "%s%d%%";
+  varfim;
+  escreva M, "\n";
+fim
+"#,
+        "",
+    );
+
+    assert_eq!(out, "%s%d%%\n");
+}
@@ -0,0 +1,81 @@
+// The obj file keeps the shape of the MGol program: a 'se' is an if
+// (with an else for its 'senao') and a loop is a while, or a for (;;)
+// when its condition takes more than a comparison, without a goto.
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+// the statements of the main function of the obj file, without the
+// temporary variable declarations
+fn main_body(name: &str, source: &str, options: &[&str]) -> Vec<String> {
+    let path = common::mgol_with(name, source, options)
+        .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .skip_while(|line| !line.starts_with("int main"))
+        .skip_while(|line| !line.contains("/*----------"))
+        .skip(1)
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn se_and_enquanto_keep_their_shape() {
+    let source = "inicio
+  varinicio
+    inteiro i;
+    inteiro n;
+  varfim;
+  leia n;
+  i <- 0;
+  enquanto (i < n)
+    se (i > 1) entao
+      escreva i;
+    senao
+      escreva \"-\";
+    fimse
+    i <- i + 1;
+  fimenquanto
+fim
+";
+    assert_eq!(
+        main_body("estruturas", source, &[]),
+        [
+            "    scanf(\"%d\", &v_n);",
+            "    v_i = 0;",
+            "    while (v_i < v_n) {",
+            "        if (v_i > 1) {",
+            "            printf(\"%d\", v_i);",
+            "        } else {",
+            "            printf(\"-\");",
+            "        }",
+            "        T1 = v_i + 1;",
+            "        v_i = T1;",
+            "    }",
+            "    return 0;",
+            "}",
+        ]
+    );
+    assert_eq!(common::run("estruturas", source, "4"), "--23");
+}
+
+#[test]
+fn corpus_has_no_goto() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programas");
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "mgol") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        for options in [&[][..], &["-O"][..], &["--bounds-check"][..]] {
+            let c_path = common::mgol_with(&format!("estruturas-{}{}", name, options.concat()), &source, options)
+                .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+            let text = fs::read_to_string(c_path).unwrap();
+            assert!(!text.contains("goto"), "goto no obj file de {} com {:?}:\n{}", name, options, text);
+        }
+    }
+}
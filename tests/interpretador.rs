@@ -0,0 +1,99 @@
+// 'mgol run' runs a program without a C compiler, reading and writing
+// the same as its obj file.
+mod common;
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    inteiro n;
+    real x;
+    literal nome;
+    logico ok;
+    inteiro v[3];
+  varfim;
+  leia n;
+  leia x;
+  leia nome;
+  leia ok;
+  v[n] <- n * 2;
+  x <- x / 4.0;
+  escreva nome, \" \", v[n], \" \", x, \" \", ok, \"\\n\";
+fim
+";
+
+#[test]
+fn runs_like_the_obj_file() {
+    let input = "2 3 maria verdadeiro";
+    let (out, status) = common::interpret("interpretador", PROGRAMA, input, &[]);
+    assert_eq!(status, 0);
+    assert_eq!(out, "maria 4 0.750000 verdadeiro\n");
+    assert_eq!(common::run("interpretador", PROGRAMA, input), out);
+}
+
+#[test]
+fn index_out_of_bounds_stops_the_program() {
+    let input = "5 1.0 ana falso";
+    let (out, status) = common::interpret(
+        "interpretador-limites",
+        PROGRAMA,
+        input,
+        &["--bounds-check"],
+    );
+    assert_eq!(status, 1);
+    assert!(
+        out.starts_with("Erro: indice 5 fora dos limites do vetor [0, 3)"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn program_with_errors_does_not_run() {
+    let (out, status) = common::interpret(
+        "interpretador-erros",
+        "inicio\n  varinicio\n  varfim;\n  leia x;\nfim\n",
+        "",
+        &[],
+    );
+    assert_eq!(status, 1);
+    assert!(out.contains("[ESe"), "{}", out);
+}
+
+const RECURSAO: &str = "inicio
+  varinicio
+    inteiro n;
+  varfim;
+  funcao inteiro soma(inteiro n)
+    varinicio
+      inteiro k;
+    varfim;
+    se (n <= 0) entao
+      retorne 0;
+    fimse
+    k <- n - 1;
+    k <- soma(k);
+    retorne k + 1;
+  fimfuncao
+  leia n;
+  n <- soma(n);
+  escreva n, \"\\n\";
+fim
+";
+
+#[test]
+fn deep_recursion_runs_like_the_obj_file() {
+    let (out, status) = common::interpret("interpretador-recursao", RECURSAO, "50000", &[]);
+    assert_eq!((out.as_str(), status), ("50000\n", 0));
+    assert_eq!(common::run("interpretador-recursao", RECURSAO, "50000"), out);
+}
+
+#[test]
+fn endless_recursion_stops_the_program() {
+    let (out, status) = common::interpret(
+        "interpretador-recursao-infinita",
+        "inicio\n  varinicio\n  varfim;\n  procedimento p()\n    p();\n  fimprocedimento\n  p();\nfim\n",
+        "",
+        &[],
+    );
+    assert_eq!(status, 1);
+    assert!(out.starts_with("Erro: mais de 200000 chamadas de subprogramas em andamento"), "{}", out);
+}
@@ -0,0 +1,67 @@
+// With --emit=ir the compiler writes the three-address code of the
+// program instead of the obj file.
+mod common;
+
+use std::fs;
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    inteiro a;
+    inteiro v[3];
+  varfim;
+  leia a;
+  se (a > 2) entao
+    escreva \"grande\";
+  senao
+    escreva a;
+  fimse
+  v[a] <- a + 1;
+fim
+";
+
+fn ir(name: &str, options: &[&str]) -> Vec<String> {
+    let path = common::mgol_with(name, PROGRAMA, options)
+        .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn statements_become_three_address_code() {
+    let lines = ir("ir", &["--emit=ir"]);
+    let code: Vec<&str> = lines
+        .iter()
+        .map(String::as_str)
+        .skip_while(|line| *line != "programa")
+        .collect();
+    assert_eq!(
+        code,
+        [
+            "programa",
+            "    temp logico T0",
+            "    temp inteiro T1",
+            "    read a (inteiro)",
+            "    T0 = a > 2",
+            "    if_false T0 goto L0",
+            "    write \"grande\" (literal)",
+            "    goto L1",
+            "L0:",
+            "    write a (inteiro)",
+            "L1:",
+            "    T1 = a + 1",
+            "    v[a] = T1",
+            "fim",
+        ]
+    );
+}
+
+#[test]
+fn bounds_check_is_an_instruction() {
+    let lines = ir("ir-bounds-check", &["--emit=ir", "--bounds-check"]);
+    let check = lines.iter().position(|line| line.starts_with("    check_index a, 3 "));
+    let access = lines.iter().position(|line| line == "    v[a] = T1");
+    assert!(check.is_some() && check < access, "{:#?}", lines);
+}
@@ -0,0 +1,104 @@
+// 'enquanto' and 'para' may be used inside any block, and a 'para'
+// with a step of 0, which would never end, is an error. The step may
+// have a sign, since a 'num' has none.
+mod common;
+
+#[test]
+fn loops_inside_blocks() {
+    let source = "inicio
+  varinicio
+    inteiro i;
+    inteiro n;
+  varfim;
+  leia n;
+  se (n > 0) entao
+    para i de 1 ate n
+      escreva i;
+    fimpara
+  senao
+    enquanto (n < 0)
+      escreva n;
+      n <- n + 1;
+    fimenquanto
+  fimse
+  escreva \"\\n\";
+fim
+";
+    assert_eq!(common::run("lacos-se", source, "3"), "123\n");
+    assert_eq!(common::run("lacos-senao", source, "-2"), "-2-1\n");
+}
+
+#[test]
+fn step_of_zero() {
+    let out = common::mgol_stdout(
+        "lacos-passo-zero",
+        "inicio
+  varinicio
+    inteiro i;
+  varfim;
+  para i de 1 ate 3 passo 0
+    escreva i;
+  fimpara
+fim
+",
+        &[],
+    );
+    assert!(out.contains("[ESe26] Erro: O passo do 'para' não pode ser 0 na linha 6"), "{}", out);
+}
+
+#[test]
+fn step_of_zero_in_a_constant() {
+    let out = common::mgol_stdout(
+        "lacos-passo-constante",
+        "inicio
+  varinicio
+    inteiro i;
+    constante PASSO <- 0;
+  varfim;
+  para i de 1 ate 3 passo PASSO
+    escreva i;
+  fimpara
+fim
+",
+        &[],
+    );
+    assert!(out.contains("[ESe26]"), "{}", out);
+}
+
+#[test]
+fn step_with_a_sign() {
+    let source = "inicio
+  varinicio
+    inteiro i;
+    inteiro p;
+  varfim;
+  leia p;
+  para i de 3 ate 1 passo -1
+    escreva i;
+  fimpara
+  escreva \" \";
+  para i de 6 ate 1 passo -p
+    escreva i;
+  fimpara
+  escreva \" \";
+  para i de 1 ate 3 passo +p
+    escreva i;
+  fimpara
+  escreva \"\\n\";
+fim
+";
+    assert_eq!(common::run("lacos-passo-sinal", source, "2"), "321 642 13\n");
+    let (out, status) = common::interpret("lacos-passo-sinal", source, "2", &[]);
+    assert_eq!((out.as_str(), status), ("321 642 13\n", 0));
+
+    let out = common::mgol_stdout(
+        "lacos-passo-sinal-invalido",
+        "inicio\n  varinicio\n    inteiro i;\n  varfim;\n  para i de 1 ate 3 passo * 2\n  fimpara\nfim\n",
+        &[],
+    );
+    assert!(
+        out.contains("[ESe27] Erro: O passo do 'para' só pode ter o sinal '+' ou '-' na linha 6"),
+        "{}",
+        out
+    );
+}
@@ -0,0 +1,202 @@
+// The scanner reads the source file as the student's editor shows it,
+// and its errors are reported where they start without stopping the
+// compilation.
+mod common;
+
+#[test]
+fn accented_characters_in_literals_and_comments() {
+    let obj_file = common::obj_file(
+        "acentos",
+        "inicio
+  varinicio
+  varfim;
+  { comentário com acentuação: ç, ã, é, ü, ñ, €, 日本 }
+  escreva \"Atenção: \", \"coração\", \" → ok\";
+fim
+",
+    );
+    for text in ["Atenção: ", "coração", " → ok"] {
+        assert!(obj_file.contains(text), "{}", obj_file);
+    }
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    // the '@' is in the 19th column, after 6 accented characters
+    let out = common::mgol_stdout(
+        "acentos-colunas",
+        "inicio\n  varinicio\n  varfim;\n  escreva \"ãéíõú€\"@;\nfim\n",
+        &[],
+    );
+    assert!(out.contains("[EL0] Erro léxico na linha 4, coluna 19"), "{}", out);
+}
+
+#[test]
+fn accented_identifier_is_reported() {
+    let out = common::mgol_stdout(
+        "acentos-identificador",
+        "inicio\n  varinicio\n    inteiro ação;\n  varfim;\nfim\n",
+        &[],
+    );
+    assert!(out.contains("[EL6] Erro léxico na linha 3, coluna 14"), "{}", out);
+}
+
+#[test]
+fn tabs_go_to_the_next_tab_stop() {
+    let source = "inicio\n\tvarinicio\n\tvarfim;\n\tescreva\t@;\nfim\n";
+    // with the default width of 4, escreva starts in the 5th column
+    // and the tab after it goes to the 13th
+    let out = common::mgol_stdout("tabulacao", source, &[]);
+    assert!(out.contains("linha 4, coluna 13"), "{}", out);
+
+    let out = common::mgol_stdout("tabulacao-8", source, &["--tab-width=8"]);
+    assert!(out.contains("linha 4, coluna 17"), "{}", out);
+}
+
+#[test]
+fn crlf_line_ends_and_bom_are_ignored() {
+    let obj_file = common::obj_file(
+        "crlf-bom",
+        "\u{feff}inicio\r\n  varinicio\r\n  varfim;\r\n  escreva \"fim\";\r\nfim\r\n",
+    );
+    assert!(obj_file.contains("\"fim\""), "{}", obj_file);
+    assert!(!obj_file.contains('\r'), "{}", obj_file);
+
+    // an error in the first line is where the editor shows it
+    let out = common::mgol_stdout("crlf-bom-erro", "\u{feff}inicio @\r\nfim\r\n", &[]);
+    assert!(out.contains("linha 1, coluna 8"), "{}", out);
+}
+
+// "ação" with a '€' and a '“', saved on Windows with a BOM
+const WINDOWS_SOURCE: &[u8] =
+    b"\xef\xbb\xbfinicio\r\n  varinicio\r\n  varfim;\r\n  escreva \"a\xe7\xe3o \x80 \x93\";\r\nfim\r\n";
+
+#[test]
+fn latin1_and_cp1252_sources_are_decoded() {
+    let obj_file = |name, encoding| {
+        let path = common::mgol_bytes(name, WINDOWS_SOURCE, &[encoding])
+            .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+        std::fs::read_to_string(path).unwrap()
+    };
+
+    let out = obj_file("latin1", "--encoding=latin1");
+    assert!(out.contains("\"ação \u{80} \u{93}\""), "{}", out);
+
+    let out = obj_file("cp1252", "--encoding=cp1252");
+    assert!(out.contains("\"ação € “\""), "{}", out);
+}
+
+#[test]
+fn invalid_utf8_is_reported_with_a_hint() {
+    let out = common::mgol_bytes("utf8-invalido", WINDOWS_SOURCE, &[]).unwrap_err();
+    // the first invalid byte is the 'ç', in the 13th column
+    assert!(
+        out.contains("[EL7] Erro léxico na linha 4, coluna 13: o arquivo não está em UTF-8"),
+        "{}",
+        out
+    );
+    assert!(out.contains("--encoding=cp1252"), "{}", out);
+    assert!(out.contains("Foi encontrado 1 erro léxico"), "{}", out);
+}
+
+#[test]
+fn escape_sequences_in_literals() {
+    let obj_file = common::obj_file(
+        "escapes",
+        r#"inicio
+  varinicio
+  varfim;
+  escreva "1\t2\n", "\"a\\b\"", "\n", "fim \\n\n";
+fim
+"#,
+    );
+    for text in [r"1\t2\n", r#"\"a\\b\""#, r"fim \\n\n"] {
+        assert!(obj_file.contains(text), "{}", obj_file);
+    }
+}
+
+#[test]
+fn unknown_escape_sequence_is_reported() {
+    let out = common::mgol_stdout(
+        "escape-desconhecido",
+        "inicio\n  varinicio\n  varfim;\n  escreva \"a\\qb\";\n  escreva \"\\x\";\nfim\n",
+        &[],
+    );
+    for (row, col, c) in [(4, 14, 'q'), (5, 13, 'x')] {
+        let msg = format!(
+            "[EL8] Erro léxico na linha {}, coluna {}: sequência de escape desconhecida '\\{}'",
+            row, col, c
+        );
+        assert!(out.contains(&msg), "{}", out);
+    }
+    // the rest of each literal is still read
+    assert!(out.contains("Foi encontrado 2 erros léxicos"), "{}", out);
+}
+
+#[test]
+fn line_and_nested_block_comments() {
+    let source = "inicio // o programa
+  varinicio // as variáveis
+    inteiro a; { um { comentário { dentro } de } outro }
+  varfim;
+  {
+    a <- 1; { ainda comentado }
+    escreva a;
+  }
+  a <- 2; // a <- 3;
+  escreva a, \" { não é comentário } // nem este\\n\";
+fim // sem fim de linha";
+    assert_eq!(common::run("comentarios", source, ""), "2 { não é comentário } // nem este\n");
+}
+
+#[test]
+fn unterminated_block_comment_is_reported_where_it_opens() {
+    let out = common::mgol_stdout(
+        "comentario-aberto",
+        "inicio\n  varinicio\n  varfim;\n  { um { dois }\n  escreva \"a\";\nfim\n",
+        &[],
+    );
+    assert!(
+        out.contains("[EL5] Erro léxico na linha 4, coluna 3: comentário aberto aqui não foi fechado com '}' até o fim do arquivo"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn unterminated_literal_is_closed_at_the_end_of_its_line() {
+    // The literal is reported where it opens and the next lines are
+    // read as usual: the literal between them is not taken as open, and
+    // the errors after them are still found. The ';' inside the literal
+    // is missing for the parser, which goes on.
+    let out = common::mgol_stdout(
+        "literal-aberto",
+        "inicio\n  varinicio\n  varfim;\n  escreva \"abc;\n  escreva \"def\";\n  escreva @\"ghi;\nfim\n",
+        &[],
+    );
+    for msg in [
+        "[EL9] Erro léxico na linha 4, coluna 11: literal aberto aqui não foi fechado com '\"' antes do fim da linha",
+        "[EL0] Erro léxico na linha 6, coluna 11: '@' não pertence ao alfabeto",
+        "[EL9] Erro léxico na linha 6, coluna 12",
+        "Foi encontrado 3 erros léxicos",
+        "[ES2] Erro sintático na linha 5, coluna 9: ausência de ';'",
+        "[ES2] Erro sintático na linha 7, coluna 3: ausência de ';'",
+        "Foi encontrado 2 erros sintáticos",
+    ] {
+        assert!(out.contains(msg), "{}", out);
+    }
+}
+
+#[test]
+fn unterminated_literal_at_the_end_of_the_file() {
+    let out = common::mgol_stdout(
+        "literal-aberto-eof",
+        "inicio\n  varinicio\n  varfim;\n  escreva \"abc",
+        &[],
+    );
+    assert!(
+        out.contains("[EL5] Erro léxico na linha 4, coluna 11: literal aberto aqui não foi fechado com '\"' até o fim do arquivo"),
+        "{}",
+        out
+    );
+}
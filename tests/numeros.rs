@@ -0,0 +1,81 @@
+// Numeric literals must fit in their type (ESe25) and are written in a
+// canonical form; a literal constant reduces by the same rule and must
+// be kept as it is.
+mod common;
+
+#[test]
+fn literal_assigned_to_a_variable() {
+    let source = "inicio
+  varinicio
+    literal s;
+    literal t;
+  varfim;
+  s <- \"abc\";
+  t <- s;
+  escreva s, \" \", t, \"\\n\";
+fim
+";
+    assert_eq!(common::run("literal-atribuido", source, ""), "abc abc\n");
+    let (out, status) = common::interpret("literal-atribuido-run", source, "", &[]);
+    assert_eq!(status, 0);
+    assert_eq!(out, "abc abc\n");
+}
+
+fn range_error(name: &str, tk_type: &str, number: &str) -> String {
+    common::mgol_stdout(
+        name,
+        &format!(
+            "inicio\n  varinicio\n    {} x;\n  varfim;\n  x <- {};\n  escreva x;\nfim\n",
+            tk_type, number
+        ),
+        &[],
+    )
+}
+
+#[test]
+fn numbers_at_the_limits_of_their_type() {
+    let source = "inicio
+  varinicio
+    inteiro n;
+    real x;
+  varfim;
+  n <- 2147483647;
+  x <- 1.7976931348623157e308;
+  escreva n, \" \", x, \"\\n\";
+  n <- 0002147483647;
+  escreva n, \"\\n\";
+fim
+";
+    let out = common::mgol_stdout("numeros-limites", source, &[]);
+    assert!(!out.contains("[ESe25]"), "{}", out);
+    let (out, status) = common::interpret("numeros-limites-run", source, "", &[]);
+    assert_eq!(status, 0);
+    assert!(out.starts_with("2147483647 1797693134862315"), "{}", out);
+    assert!(out.ends_with("\n2147483647\n"), "{}", out);
+}
+
+#[test]
+fn integer_out_of_range() {
+    let out = range_error("inteiro-fora", "inteiro", "2147483648");
+    assert!(
+        out.contains("[ESe25] Erro: O número '2147483648' está fora do intervalo do tipo inteiro na linha 5"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn real_out_of_range() {
+    let out = range_error("real-fora", "real", "1.0e309");
+    assert!(
+        out.contains("[ESe25] Erro: O número '1.0e309' está fora do intervalo do tipo real na linha 5"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn program_with_a_number_out_of_range_is_not_compiled() {
+    let source = "inicio\n  varinicio\n    inteiro x;\n  varfim;\n  x <- 99999999999;\nfim\n";
+    assert!(common::mgol("numero-fora", source).is_err());
+}
@@ -0,0 +1,78 @@
+// With -O the intermediate code is optimized and the compiler tells
+// which optimizations were applied; the program must still write the
+// same as without them.
+mod common;
+
+use std::fs;
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    inteiro a;
+    inteiro b;
+    real c;
+  varfim;
+  leia a;
+  b <- 2 + 3;
+  c <- 1.5 * 2.0;
+  c <- c + 2e0;
+  se (b > 4) entao
+    escreva \"sim\\n\";
+  fimse
+  se (b < 0) entao
+    escreva \"nunca\\n\";
+  fimse
+  escreva a, \" \", b, \" \", c, \"\\n\";
+fim
+";
+
+#[test]
+fn optimizations_are_reported() {
+    let out = common::mgol_stdout("otimizacao-relatorio", PROGRAMA, &["-O"]);
+    for line in [
+        "Otimizações aplicadas:",
+        "    dobramento de constantes: ",
+        "    propagação de cópias: ",
+        "    eliminação de temporárias mortas: ",
+        "    remoção de desvios constantes: 2",
+        "    remoção de código inalcançável: 1",
+    ] {
+        assert!(out.lines().any(|l| l.starts_with(line)), "{:?} não está em:\n{}", line, out);
+    }
+
+    let source = "inicio\n  varinicio\n  varfim;\n  escreva \"oi\";\nfim\n";
+    let out = common::mgol_stdout("otimizacao-nenhuma", source, &["-O"]);
+    assert!(out.contains("Nenhuma otimização foi aplicada"), "{}", out);
+}
+
+#[test]
+fn optimized_code() {
+    let path = common::mgol_with("otimizacao-ir", PROGRAMA, &["-O", "--emit=ir"])
+        .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    let ir = fs::read_to_string(path).unwrap();
+    let code: Vec<&str> = ir.lines().skip_while(|line| *line != "programa").collect();
+    assert_eq!(
+        code,
+        [
+            "programa",
+            "    read a (inteiro)",
+            "    b = 5",
+            "    c = 3.0",
+            "    c = 5.0",
+            "    write \"sim\\n\" (literal)",
+            "    write a (inteiro)",
+            "    write \" \" (literal)",
+            "    write 5 (inteiro)",
+            "    write \" \" (literal)",
+            "    write 5.0 (real)",
+            "    write \"\\n\" (literal)",
+            "fim",
+        ]
+    );
+}
+
+#[test]
+fn optimized_program_writes_the_same() {
+    let out = common::run("otimizacao-sem", PROGRAMA, "7");
+    assert_eq!(out, "sim\n7 5 5.000000\n");
+    assert_eq!(common::run_with("otimizacao-com", PROGRAMA, "7", &["-O"]), out);
+}
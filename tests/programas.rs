@@ -0,0 +1,49 @@
+// Every MGol program in tests/programas is compiled, with and without
+// --bounds-check and -O, and the obj file must be accepted by cc with
+// -Wall -Werror. A program named x.mgol runs with x.entrada as its
+// input (when there is one) and must write exactly what is in x.saida,
+// and the optimized program, as well as the program run by 'mgol run'
+// and its bytecode run by the VM, must write the same as the other one.
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn corpus_compiles_and_runs() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programas");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mgol"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "nenhum programa em {}", dir.display());
+
+    for path in programs {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        let input = fs::read_to_string(path.with_extension("entrada")).unwrap_or_default();
+        let expected = fs::read_to_string(path.with_extension("saida")).ok();
+
+        for options in [&[][..], &["--bounds-check"][..]] {
+            let test_name = format!("programa-{}{}", name, options.concat());
+            let out = common::run_with(&test_name, &source, &input, options);
+
+            if let Some(expected) = &expected {
+                assert_eq!(&out, expected, "saída de {} com {:?}", name, options);
+            }
+
+            let optimized_options = [options, &["-O"]].concat();
+            let optimized = common::run_with(&format!("{}-O", test_name), &source, &input, &optimized_options);
+            assert_eq!(optimized, out, "saída de {} com {:?}", name, optimized_options);
+
+            let (interpreted, _) = common::interpret(&format!("{}-run", test_name), &source, &input, options);
+            assert_eq!(interpreted, out, "saída de mgol run {} com {:?}", name, options);
+
+            let mgb_path = common::bytecode(&format!("{}-mgb", test_name), &source, options);
+            let (vm_out, _) = common::run_bytecode(&mgb_path, &input);
+            assert_eq!(vm_out, out, "saída do bytecode de {} com {:?}", name, options);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+// The temporary variables are reused once their values are no longer
+// needed, each type with its own ones, and a copy needs none. They
+// never take the place of a variable of the program with the same name.
+mod common;
+
+use std::fs;
+
+// the temporary variable declarations of the intermediate code; the
+// obj file leaves out the ones of the comparisons of its if and while
+fn temps(name: &str, source: &str) -> Vec<String> {
+    let path = common::mgol_with(name, source, &["--emit=ir"])
+        .unwrap_or_else(|msgs| panic!("erros na compilação:\n{}", msgs));
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("    temp "))
+        .map(|line| String::from(line.trim()))
+        .collect()
+}
+
+#[test]
+fn temps_are_reused_by_type() {
+    let source = "inicio
+  varinicio
+    inteiro a;
+    real r;
+  varfim;
+  leia a;
+  a <- a + 1;
+  a <- a * 2;
+  a <- a - 3;
+  r <- 1.5;
+  r <- r * 2.0;
+  r <- r / 4.0;
+  se (a > 2) entao
+    se (r < 1.0) entao
+      escreva a, r;
+    fimse
+  fimse
+fim
+";
+    assert_eq!(temps("temporarias-tipos", source), ["temp inteiro T0", "temp real T1", "temp logico T2"]);
+
+    let out = common::run("temporarias-tipos", source, "4");
+    assert_eq!(out, "70.750000");
+}
+
+#[test]
+fn copy_needs_no_temp() {
+    let source = "inicio
+  varinicio
+    inteiro x;
+    inteiro y;
+  varfim;
+  leia y;
+  x <- y;
+  escreva x;
+fim
+";
+    assert!(temps("temporarias-copia", source).is_empty());
+}
+
+#[test]
+fn variable_named_like_a_temp() {
+    let source = "inicio
+  varinicio
+    inteiro T0;
+    inteiro a;
+  varfim;
+  T0 <- 5;
+  a <- T0 + 1;
+  a <- a * 2;
+  escreva T0, \" \", a;
+fim
+";
+    assert_eq!(common::run("temporarias-nome", source, ""), "5 12");
+    assert_eq!(common::run_with("temporarias-nome-O", source, "", &["-O"]), "5 12");
+    assert_eq!(common::interpret("temporarias-nome", source, "", &[]), (String::from("5 12"), 0));
+
+    let mgb_path = common::bytecode("temporarias-nome", source, &[]);
+    assert_eq!(common::run_bytecode(&mgb_path, ""), (String::from("5 12"), 0));
+    assert_eq!(common::run_asm("temporarias-nome", source, "", &[]), (String::from("5 12"), 0));
+}
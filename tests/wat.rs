@@ -0,0 +1,218 @@
+// A program compiled with --emit=wat is a WebAssembly module that
+// imports its reads and writes. The names and the labels of the
+// modules of the programs in tests/programas are checked here; with
+// wat2wasm and node installed, 'cargo test -- --ignored' also runs
+// them with the host in tests/wat/executa.js, and they must write the
+// same as 'mgol run'.
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const PROGRAMA: &str = "inicio
+  varinicio
+    literal nome;
+    inteiro v[3];
+    inteiro n;
+    inteiro z;
+  varfim;
+  procedimento mostra(literal s, inteiro k)
+    varinicio
+      literal t;
+      real w[2];
+    varfim;
+    t <- s;
+    w[1] <- 2.5;
+    escreva t, \" \", w[1], \" \", w[0], \"\\n\";
+    se (k > 0) entao
+      leia t;
+      mostra(t, k - 1);
+      escreva t, \"\\n\";
+    fimse
+  fimprocedimento
+  leia nome;
+  mostra(nome, 2);
+  escreva nome, \"\\n\";
+  leia n;
+  v[n] <- 5;
+  escreva v[n], \"\\n\";
+  leia z;
+  n <- n / z;
+  escreva n;
+fim
+";
+
+fn installed(tool: &str) -> bool {
+    Command::new(tool).arg("--version").output().is_ok_and(|out| out.status.success())
+}
+
+// assemble a .wat file with wat2wasm and run it with node, returning
+// what it wrote and its exit status
+fn run_wat(wat_path: &Path, input: &str) -> (String, i32) {
+    let wasm_path = wat_path.with_extension("wasm");
+    let out = Command::new("wat2wasm").arg(wat_path).arg("-o").arg(&wasm_path).output().unwrap();
+    assert!(
+        out.status.success(),
+        "wat2wasm rejeitou o módulo gerado:\n{}\n{}",
+        String::from_utf8_lossy(&out.stderr),
+        fs::read_to_string(wat_path).unwrap()
+    );
+
+    let host = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("wat").join("executa.js");
+    let mut child = Command::new("node")
+        .arg(host)
+        .arg(&wasm_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    (
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+        out.status.code().unwrap_or(-1),
+    )
+}
+
+#[test]
+fn module_imports_the_io() {
+    let text = fs::read_to_string(common::wat("wat", PROGRAMA, &[])).unwrap();
+    assert!(text.starts_with("(module\n"), "{}", text);
+    assert!(text.contains("(import \"mgol\" \"leia_literal\" (func $mgol.leia_literal (param i32)))"));
+    assert!(text.contains("(import \"mgol\" \"escreva_real\" (func $mgol.escreva_real (param f64)))"));
+    assert!(text.contains("(memory (export \"memory\")"));
+    assert!(text.contains("(func $mgol.programa (export \"main\")"));
+
+    // the literals and the arrays are addresses in memory
+    assert!(text.contains("(global $n (mut i32) (i32.const 0))"), "{}", text);
+    assert!(text.contains("(global $v i32 (i32.const"), "{}", text);
+    assert!(text.contains("    (param $s i32)\n    (param $k i32)\n"), "{}", text);
+    assert!(text.contains("    (local $t i32)\n    (local $w i32)\n"), "{}", text);
+    assert!(text.contains("f64.store"), "{}", text);
+    assert!(text.contains("call $mgol.divide"), "{}", text);
+
+    let opened = text.matches('(').count() - text.matches("\"(").count();
+    let closed = text.matches(')').count() - text.matches(")\"").count();
+    assert_eq!(opened, closed, "{}", text);
+}
+
+// Check the names of a module the way wat2wasm does: each local, global
+// and function used is declared once, and each label a branch goes to
+// is the one of an enclosing block or loop.
+fn check_names(text: &str) -> Result<(), String> {
+    let declared = |kind: &str| -> Vec<String> {
+        text.match_indices(&format!("({} $", kind))
+            .map(|(i, m)| text[i + m.len() - 1..].split([' ', ')', '\n']).next().unwrap().to_string())
+            .collect()
+    };
+    let globals = declared("global");
+    let functions = declared("func");
+
+    for (n, function) in text.split("\n  (func ").enumerate().skip(1) {
+        let mut locals: Vec<&str> = Vec::new();
+        let mut labels: Vec<&str> = Vec::new();
+        for line in function.lines().map(str::trim) {
+            for (i, m) in line.match_indices("(param $").chain(line.match_indices("(local $")) {
+                let name = line[i + m.len() - 1..].split([' ', ')']).next().unwrap();
+                if locals.contains(&name) {
+                    return Err(format!("{} declarada duas vezes na função {}", name, n));
+                }
+                locals.push(name);
+            }
+
+            let words: Vec<&str> = line.split(' ').collect();
+            match words[..] {
+                ["local.get" | "local.set" | "local.tee", name] if !locals.contains(&name) => {
+                    return Err(format!("{} não declarada na função {}", name, n));
+                }
+                ["global.get" | "global.set", name] if !globals.iter().any(|global| global == name) => {
+                    return Err(format!("global {} não declarada", name));
+                }
+                ["call", name] if !functions.iter().any(|function| function == name) => {
+                    return Err(format!("função {} não declarada", name));
+                }
+                ["block" | "loop", label] => labels.push(label),
+                ["end", label] if labels.pop() != Some(label) => {
+                    return Err(format!("{} fecha outro bloco na função {}", label, n));
+                }
+                ["br" | "br_if" | "br_table", ref targets @ ..] => {
+                    if let Some(target) = targets.iter().find(|target| !labels.contains(target)) {
+                        return Err(format!("desvio para {} fora do bloco na função {}", target, n));
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Some(label) = labels.last() {
+            return Err(format!("{} não foi fechado na função {}", label, n));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn modules_are_well_formed() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programas");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mgol"))
+        .collect();
+    programs.sort();
+    programs.push(PathBuf::new());
+
+    for path in programs {
+        // the empty path is PROGRAMA
+        let (name, source) = match path.file_stem() {
+            Some(name) => (name.to_str().unwrap(), fs::read_to_string(&path).unwrap()),
+            None => ("programa", String::from(PROGRAMA)),
+        };
+        for options in [&[][..], &["-O"][..], &["--bounds-check"][..]] {
+            let wat_path = common::wat(&format!("wat-nomes-{}{}", name, options.concat()), &source, options);
+            let text = fs::read_to_string(wat_path).unwrap();
+            if let Err(msg) = check_names(&text) {
+                panic!("módulo de {} com {:?}: {}\n{}", name, options, msg, text);
+            }
+        }
+    }
+}
+
+#[test]
+#[ignore = "precisa do wat2wasm e do node"]
+fn modules_run_like_the_interpreter() {
+    assert!(
+        installed("wat2wasm") && installed("node"),
+        "wat2wasm e node devem estar instalados para executar os módulos"
+    );
+
+    for options in [&[][..], &["--bounds-check"][..]] {
+        let wat_path = common::wat(&format!("wat-run{}", options.concat()), PROGRAMA, options);
+        for input in ["ana bia cid 2 1", "ana bia cid 3 1", "ana bia cid 1 0"] {
+            let out = run_wat(&wat_path, input);
+            assert_eq!(out, common::interpret("wat-interpretador", PROGRAMA, input, options), "entrada {}", input);
+        }
+    }
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programas");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mgol"))
+        .collect();
+    programs.sort();
+
+    for path in programs {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        let input = fs::read_to_string(path.with_extension("entrada")).unwrap_or_default();
+        for options in [&[][..], &["-O"][..]] {
+            let wat_path = common::wat(&format!("wat-{}{}", name, options.concat()), &source, options);
+            let (out, _) = run_wat(&wat_path, &input);
+            let (expected, _) = common::interpret(&format!("wat-{}-run", name), &source, &input, &[]);
+            assert_eq!(out, expected, "saída do módulo de {} com {:?}", name, options);
+        }
+    }
+}
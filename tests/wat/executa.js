@@ -0,0 +1,90 @@
+// Run a MGol program compiled to WebAssembly (with --emit=wat and then
+// wat2wasm): node executa.js programa.wasm < entrada
+//
+// The host functions read the standard input like scanf and write like
+// printf, the same as the obj file of the program, and the exit status
+// is 1 when the program stopped because of an error.
+const fs = require('fs');
+
+const input = fs.readFileSync(0);
+let pos = 0;
+let output = '';
+let memory;
+
+// the next input bytes for which accept is true, after the white space
+function readWhile(accept) {
+  while (pos < input.length && /\s/.test(String.fromCharCode(input[pos]))) {
+    pos++;
+  }
+  const start = pos;
+  while (pos < input.length && accept(input[pos], input.subarray(start, pos))) {
+    pos++;
+  }
+  return input.subarray(start, pos).toString('utf8');
+}
+
+const isSpace = (c) => /\s/.test(String.fromCharCode(c));
+const isDigit = (c) => c >= 0x30 && c <= 0x39;
+
+// the text ending in a '\0' at an address of the memory
+function text(address) {
+  const bytes = new Uint8Array(memory.buffer);
+  let end = address;
+  while (bytes[end] !== 0) {
+    end++;
+  }
+  return Buffer.from(bytes.subarray(address, end)).toString('utf8');
+}
+
+const mgol = {
+  escreva_inteiro: (n) => { output += n; },
+  escreva_real: (x) => { output += Number.isNaN(x) ? 'nan' : x.toFixed(6); },
+  escreva_literal: (address) => { output += text(address); },
+  escreva_logico: (n) => { output += n ? 'verdadeiro' : 'falso'; },
+  leia_inteiro: (value) => {
+    const s = readWhile((c, read) => isDigit(c) || (read.length === 0 && (c === 0x2d || c === 0x2b)));
+    const n = parseInt(s, 10);
+    return Number.isNaN(n) ? value : n | 0;
+  },
+  leia_real: (value) => {
+    const s = readWhile((c, read) => {
+      const last = read[read.length - 1];
+      if (isDigit(c) || c === 0x2e) return true;
+      if (c === 0x65 || c === 0x45) return read.length > 0;
+      if (c === 0x2d || c === 0x2b) return read.length === 0 || last === 0x65 || last === 0x45;
+      return false;
+    });
+    const x = parseFloat(s);
+    return Number.isNaN(x) ? value : x;
+  },
+  leia_literal: (address) => {
+    const s = Buffer.from(readWhile((c) => !isSpace(c))).subarray(0, 255);
+    if (s.length > 0) {
+      new Uint8Array(memory.buffer).set([...s, 0], address);
+    }
+  },
+  leia_logico: (value) => {
+    const s = readWhile((c) => !isSpace(c));
+    return s === '' ? value : Number(s === 'verdadeiro');
+  },
+  erro_indice: (i, size, line) => {
+    output += `Erro: indice ${i} fora dos limites do vetor [0, ${size})`;
+    output += line > 0 ? ` na linha ${line}\n` : '\n';
+  },
+  erro_divisao: () => { output += 'Erro: divisão por zero\n'; },
+};
+
+WebAssembly.instantiate(fs.readFileSync(process.argv[2]), { mgol }).then(({ instance }) => {
+  memory = instance.exports.memory;
+  let status = 0;
+  try {
+    instance.exports.main();
+  } catch (e) {
+    if (!(e instanceof WebAssembly.RuntimeError)) {
+      throw e;
+    }
+    status = 1;
+  }
+  process.stdout.write(output);
+  process.exitCode = status;
+});